@@ -0,0 +1,201 @@
+// Moving files out to an archive location, or packing them into a zip, instead of
+// deleting them
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+// Where a scanned file should live under the archive root. Files keep their path
+// relative to the scan target they were found in, under a folder named after that
+// target (e.g. `Downloads/old/setup.zip`). Anything outside the known targets keeps
+// its full path minus the drive/root.
+pub fn relative_archive_path(file: &Path, scan_roots: &[PathBuf]) -> PathBuf {
+    for root in scan_roots {
+        if let Ok(relative) = file.strip_prefix(root) {
+            let root_name = root.file_name().map(PathBuf::from).unwrap_or_default();
+            return root_name.join(relative);
+        }
+    }
+
+    file.components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect()
+}
+
+// Renames when possible and falls back to copy+remove across filesystems. The copy
+// keeps the original timestamps and is checked for a matching length before the
+// source is removed.
+pub fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if to.exists() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, "destination already exists"));
+    }
+
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    let metadata = fs::metadata(from)?;
+    let copied = fs::copy(from, to)?;
+    if copied != metadata.len() {
+        let _ = fs::remove_file(to);
+        return Err(io::Error::other("copy was incomplete"));
+    }
+
+    filetime::set_file_times(
+        to,
+        filetime::FileTime::from_last_access_time(&metadata),
+        filetime::FileTime::from_last_modification_time(&metadata),
+    )?;
+    fs::remove_file(from)
+}
+
+// How one file's move went: its size, or why it's still where it was
+pub struct ArchiveOutcome {
+    pub path: PathBuf,
+    pub moved: Result<u64, String>,
+}
+
+// Moves files into the archive on a worker thread, reporting each one as it goes. The
+// worker stops after the file it's on once this is dropped.
+pub struct Archiving {
+    receiver: Receiver<ArchiveOutcome>,
+    pub destination: PathBuf,
+    pub total: usize,
+    pub outcomes: Vec<ArchiveOutcome>,
+}
+
+impl Archiving {
+    pub fn start(files: Vec<PathBuf>, scan_roots: Vec<PathBuf>, destination: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let total = files.len();
+
+        let worker_destination = destination.clone();
+        std::thread::spawn(move || {
+            for path in files {
+                let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                let target = worker_destination.join(relative_archive_path(&path, &scan_roots));
+                let moved = move_file(&path, &target).map(|()| size).map_err(|e| e.to_string());
+                if sender.send(ArchiveOutcome { path, moved }).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Self { receiver, destination, total, outcomes: Vec::with_capacity(total) }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        self.outcomes.len() as f32 / self.total as f32
+    }
+
+    // Takes in whatever the worker has sent since the last frame. Returns true once it's done.
+    pub fn poll(&mut self) -> bool {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(outcome) => self.outcomes.push(outcome),
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => return true,
+            }
+        }
+    }
+}
+
+// Packs `files` into a new zip at `zip_path`, each under the path
+// `relative_archive_path` gives it; bundles go in with everything inside them.
+// Returns the size of the finished zip. On failure the partial zip is removed.
+pub fn zip_files(files: &[PathBuf], scan_roots: &[PathBuf], zip_path: &Path) -> io::Result<u64> {
+    if let Err(e) = write_zip(files, scan_roots, zip_path) {
+        let _ = fs::remove_file(zip_path);
+        return Err(e);
+    }
+    Ok(fs::metadata(zip_path)?.len())
+}
+
+// Writes a zip on a worker thread so the window stays responsive while it's packed
+pub struct Zipping {
+    receiver: Receiver<io::Result<u64>>,
+    pub files: Vec<PathBuf>,
+    pub zip_path: PathBuf,
+}
+
+impl Zipping {
+    pub fn start(files: Vec<PathBuf>, scan_roots: Vec<PathBuf>, zip_path: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let worker_files = files.clone();
+        let worker_path = zip_path.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(zip_files(&worker_files, &scan_roots, &worker_path));
+        });
+
+        Self { receiver, files, zip_path }
+    }
+
+    // The size of the finished zip, or why it couldn't be written, once the worker is done
+    pub fn poll(&self) -> Option<io::Result<u64>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(io::Error::other("the zip worker stopped"))),
+        }
+    }
+}
+
+fn write_zip(files: &[PathBuf], scan_roots: &[PathBuf], zip_path: &Path) -> io::Result<()> {
+    let mut zip = zip::ZipWriter::new(fs::File::create(zip_path)?);
+    for file in files {
+        add_to_zip(&mut zip, file, &relative_archive_path(file, scan_roots))?;
+    }
+    zip.finish().map_err(io::Error::other)?;
+    Ok(())
+}
+
+fn add_to_zip(zip: &mut zip::ZipWriter<fs::File>, path: &Path, name: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            add_to_zip(zip, &entry.path(), &name.join(entry.file_name()))?;
+        }
+        return Ok(());
+    }
+    // Links inside bundles would otherwise pull in whatever they point at
+    if !metadata.is_file() {
+        return Ok(());
+    }
+
+    // Zip entry names use forward slashes whatever the platform
+    let entry_name = name.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let mut options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .large_file(metadata.len() >= u32::MAX as u64);
+    let modified = metadata.modified().ok().and_then(|time| {
+        zip::DateTime::try_from(chrono::DateTime::<chrono::Local>::from(time).naive_local()).ok()
+    });
+    if let Some(modified) = modified {
+        options = options.last_modified_time(modified);
+    }
+
+    zip.start_file(entry_name, options).map_err(io::Error::other)?;
+    io::copy(&mut fs::File::open(path)?, zip)?;
+    Ok(())
+}
+
+// Free space on the volume that holds `path`, walking up to the nearest existing ancestor
+pub fn available_space(path: &Path) -> io::Result<u64> {
+    let mut probe = path;
+    while !probe.exists() {
+        probe = probe.parent().ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+    }
+    fs2::available_space(probe)
+}
@@ -0,0 +1,137 @@
+// Exclude rules kept in .cleanignore files, written the way .gitignore is. A file in
+// a folder covers everything below it, rules in deeper files win over shallower
+// ones, and `!pattern` brings something back. A global file in the config
+// directory sits under them all.
+//
+// With .gitignore honored, a git project's own ignore files join the stack too,
+// the way git reads them: only inside a repository, starting from the user's
+// core.excludesFile and .git/info/exclude at its root.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+pub const FILE_NAME: &str = ".cleanignore";
+
+// e.g. ~/.config/pinnaclesort/cleanignore on Linux
+pub fn global_path() -> Option<PathBuf> {
+    Some(crate::settings::config_dir()?.join("cleanignore"))
+}
+
+// The rules in effect inside one directory, outermost first
+#[derive(Clone, Default)]
+pub struct IgnoreStack {
+    rules: Vec<Arc<Gitignore>>,
+    // Set when .gitignore files are honored, holding the user's global excludes
+    git: Option<Arc<Gitignore>>,
+    in_repository: bool,
+}
+
+impl IgnoreStack {
+    // Starts from the global file, if there is one. A rule that doesn't parse is
+    // left out and its error returned; the rest still apply.
+    pub fn global(respect_gitignore: bool) -> (Self, Vec<(PathBuf, String)>) {
+        let mut stack = Self::default();
+        let mut errors = Vec::new();
+        if let Some(path) = global_path().filter(|path| path.is_file()) {
+            stack.push(path.parent().unwrap_or(&path), &path, &mut errors);
+        }
+        if respect_gitignore {
+            let (excludes, error) = Gitignore::global();
+            if let Some(error) = error {
+                errors.push((excludes.path().to_path_buf(), error.to_string()));
+            }
+            stack.git = Some(Arc::new(excludes));
+        }
+        (stack, errors)
+    }
+
+    // The rules for a scan target, taking in the ignore files of the repository
+    // it sits in, if any, down to the folder just above it
+    pub fn for_target(&self, target: &Path) -> (Self, Vec<(PathBuf, String)>) {
+        let mut stack = self.clone();
+        let mut errors = Vec::new();
+        let repository = self.git.as_ref()
+            .and_then(|_| target.ancestors().skip(1).find(|dir| dir.join(".git").exists()));
+        if let Some(repository) = repository {
+            let mut above: Vec<&Path> = target.ancestors().skip(1).take_while(|dir| *dir != repository).collect();
+            above.push(repository);
+            for directory in above.into_iter().rev() {
+                if let Some((entered, mut failed)) = stack.enter(directory, |name| directory.join(name).exists()) {
+                    stack = entered;
+                    errors.append(&mut failed);
+                }
+            }
+        }
+        (stack, errors)
+    }
+
+    // The rules for a subdirectory, or None when it adds nothing. `has` says whether
+    // it holds an entry by that name, so the listing already read can answer.
+    pub fn enter(&self, directory: &Path, has: impl Fn(&str) -> bool) -> Option<(Self, Vec<(PathBuf, String)>)> {
+        let repository_root = self.git.is_some() && has(".git");
+        let gitignore = self.git.is_some() && (self.in_repository || repository_root) && has(".gitignore");
+        let cleanignore = has(FILE_NAME);
+        if !repository_root && !gitignore && !cleanignore {
+            return None;
+        }
+
+        let mut stack = self.clone();
+        let mut errors = Vec::new();
+        if repository_root {
+            stack.in_repository = true;
+            if let Some(excludes) = self.git.clone().filter(|excludes| !excludes.is_empty()) {
+                stack.rules.push(excludes);
+            }
+            let exclude = directory.join(".git").join("info").join("exclude");
+            if exclude.is_file() {
+                stack.push(directory, &exclude, &mut errors);
+            }
+        }
+        if gitignore {
+            stack.push(directory, &directory.join(".gitignore"), &mut errors);
+        }
+        if cleanignore {
+            stack.push(directory, &directory.join(FILE_NAME), &mut errors);
+        }
+        Some((stack, errors))
+    }
+
+    // Patterns are anchored at `root`, which for .git/info/exclude isn't the file's folder
+    fn push(&mut self, root: &Path, path: &Path, errors: &mut Vec<(PathBuf, String)>) {
+        let mut builder = GitignoreBuilder::new(root);
+        if let Some(error) = builder.add(path) {
+            errors.push((path.to_path_buf(), error.to_string()));
+        }
+        match builder.build() {
+            Ok(rules) if !rules.is_empty() => self.rules.push(Arc::new(rules)),
+            Ok(_) => {}
+            Err(error) => errors.push((path.to_path_buf(), error.to_string())),
+        }
+    }
+
+    // Inside a git project its .gitignore says what's build output, so the smart
+    // filter's build folder names aren't needed there
+    pub fn in_repository(&self) -> bool {
+        self.in_repository
+    }
+
+    // The deepest file with a rule for the path decides, as with .gitignore
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        // git's own database is never part of the project
+        if self.in_repository && is_dir && path.file_name().is_some_and(|name| name == ".git") {
+            return true;
+        }
+        for rules in self.rules.iter().rev() {
+            let matched = rules.matched(path, is_dir);
+            if matched.is_ignore() {
+                return true;
+            }
+            if matched.is_whitelist() {
+                return false;
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,162 @@
+// Headless mode: runs the same scan as the GUI and prints the results, for cron jobs and
+// scripts. Nothing is removed unless --delete is given.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use clap::Parser;
+
+use crate::deletion::{DeleteOptions, DeleteTarget, Deletion};
+use crate::scanner::{self, ScanEvent, ScanProgress, TimeUnit};
+
+#[derive(Parser)]
+#[command(name = "PinnacleSort", version, about = "Find and clean up files you haven't used in a while")]
+pub struct Args {
+    /// Run without the window and print results to stdout
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Directory to scan; repeat for several. Defaults to the saved directories.
+    #[arg(long = "dir", value_name = "PATH")]
+    pub directories: Vec<PathBuf>,
+
+    /// Flag files not used in this many days. Defaults to the saved threshold.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..=TimeUnit::Days.max_amount()))]
+    pub days: Option<u64>,
+
+    /// Move the files flagged for deletion to the Trash. Without this they're only printed.
+    #[arg(long)]
+    pub delete: bool,
+
+    /// With --delete, print what would be moved to the Trash without moving anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// With --headless, also write the full scan and the settings used to this JSON file
+    #[arg(long, value_name = "PATH")]
+    pub report: Option<PathBuf>,
+}
+
+// Settings the command line doesn't override come from the GUI's saved config
+pub fn run(args: Args) -> ExitCode {
+    let mut settings = crate::settings::load().unwrap_or_default();
+    let directories = if args.directories.is_empty() {
+        settings.search_directories().into_iter().map(PathBuf::from).collect()
+    } else {
+        args.directories
+    };
+    // Folded into the settings so the report shows what was actually used
+    if let Some(days) = args.days {
+        settings.time_limit = days;
+        settings.time_unit = TimeUnit::Days;
+    }
+
+    let options = settings.scan_options();
+
+    let volumes = crate::volumes::ExternalVolumes::detect();
+    for directory in &directories {
+        if let Some(kind) = volumes.kind_of(directory) {
+            eprintln!("note: {} is on a {} and may be slow to scan", directory.display(), kind.label());
+        }
+    }
+
+    let receiver = scanner::spawn_scan(directories.clone(), Vec::new(), options, Arc::new(ScanProgress::default()));
+
+    let mut results = Vec::new();
+    for event in receiver {
+        match event {
+            ScanEvent::Found(result) => results.push(result),
+            ScanEvent::LargeDirectory(path, entry_count) => {
+                eprintln!("skipped {} ({} entries)", path.display(), entry_count);
+            }
+            ScanEvent::Unreadable(path, error) => eprintln!("unreadable {}: {}", path, error),
+            ScanEvent::Filtered(..) => {}
+            ScanEvent::Failed(error) => {
+                eprintln!("error: {}", error);
+                return ExitCode::FAILURE;
+            }
+            ScanEvent::Finished(stats) => {
+                eprintln!("scanned {} folders in {:.1}s: {} left out by the smart filter (binary {}, system {}, build {}), {} by exclude patterns, {} as recently used",
+                    stats.directories, stats.elapsed.as_secs_f64(), stats.filtered,
+                    stats.filtered_binary, stats.filtered_system, stats.filtered_build, stats.excluded, stats.too_recent);
+                if stats.timestamp_fallbacks > 0 {
+                    eprintln!("note: {} files were judged by modified or created time instead", stats.timestamp_fallbacks);
+                }
+                break;
+            }
+        }
+    }
+    results.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+    if let Some(path) = &args.report
+        && let Err(e) = crate::report::write(path, &settings, &directories, &results)
+    {
+        eprintln!("could not write the report to {}: {}", path.display(), e);
+        return ExitCode::FAILURE;
+    }
+
+    for result in &results {
+        println!("{}\t{} days\t{} bytes\t{}",
+            result.file_path.display(), result.days_since_access, result.size_bytes, result.flag_reason);
+    }
+    if !args.delete {
+        return ExitCode::SUCCESS;
+    }
+
+    // The same worker the window uses, so associated files and bundles go the same way
+    let targets = results.iter()
+        .filter(|result| result.should_delete)
+        .map(|result| DeleteTarget {
+            path: result.file_path.clone(),
+            size_bytes: Some(result.size_bytes),
+            associated: crate::deletion::associated_files(
+                &result.file_path, &settings.associated_trigger_extension, &settings.associated_extensions),
+        })
+        .collect();
+    let options = DeleteOptions {
+        to_trash: true,
+        secure: false,
+        force_read_only: false,
+        dry_run: args.dry_run,
+        // Files found inside the Trash can't be trashed again, only removed
+        trash_directories: if settings.trash_scan_enabled { crate::trash_bin::trash_directories() } else { Vec::new() },
+    };
+    let mut deletion = Deletion::start(targets, options);
+    deletion.wait();
+
+    let mut failed = 0;
+    let mut removals = Vec::new();
+    for outcome in deletion.outcomes.drain(..) {
+        match outcome.removed {
+            Ok(removal) => {
+                for removal in std::iter::once(removal).chain(outcome.associated) {
+                    let verb = match (args.dry_run, removal.trashed) {
+                        (true, true) => "would move to Trash",
+                        (true, false) => "would delete",
+                        (false, true) => "moved to Trash",
+                        (false, false) => "deleted",
+                    };
+                    println!("{}: {}", verb, removal.path.display());
+                    removals.push(removal);
+                }
+            }
+            Err(e) => {
+                eprintln!("could not remove {}: {}", outcome.path.display(), e);
+                failed += 1;
+            }
+        }
+        for (path, e) in outcome.associated_failed {
+            eprintln!("could not remove {}, which goes with {}: {}", path.display(), outcome.path.display(), e);
+            failed += 1;
+        }
+    }
+
+    if !args.dry_run
+        && let Err(e) = crate::history::append(&removals, settings.history_max_kb * 1024)
+    {
+        eprintln!("could not write the deletion history: {}", e);
+    }
+
+    if failed > 0 { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
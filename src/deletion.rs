@@ -0,0 +1,289 @@
+// Removes the selected files on a worker thread so the window stays responsive,
+// reporting each file as it goes and stopping early when cancelled
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use crate::history::Removal;
+
+// A selected result or a whole folder, with the files that go along with it
+pub struct DeleteTarget {
+    pub path: PathBuf,
+    // What the scan counted, used for bundles since they're removed whole. None for
+    // folders, which are measured just before they go.
+    pub size_bytes: Option<u64>,
+    // Removed once the target itself is gone, see associated_files
+    pub associated: Vec<PathBuf>,
+}
+
+#[derive(Clone)]
+pub struct DeleteOptions {
+    pub to_trash: bool,
+    pub secure: bool,
+    pub force_read_only: bool,
+    // Goes through every step without removing anything
+    pub dry_run: bool,
+    // Anything found inside the Trash can only be removed for good
+    pub trash_directories: Vec<PathBuf>,
+}
+
+// How one target went, with the reason if it's still there
+pub struct DeleteOutcome {
+    pub path: PathBuf,
+    pub removed: Result<Removal, String>,
+    pub associated: Vec<Removal>,
+    // Associated files that stayed behind after the target went, with the reason
+    pub associated_failed: Vec<(PathBuf, String)>,
+    // How many of these only went once their read-only flag was cleared
+    pub forced: usize,
+}
+
+// The worker stops after the file it's on once this is cancelled or dropped
+pub struct Deletion {
+    receiver: Receiver<DeleteOutcome>,
+    cancelled: Arc<AtomicBool>,
+    pub total: usize,
+    pub outcomes: Vec<DeleteOutcome>,
+    pub options: DeleteOptions,
+}
+
+impl Deletion {
+    pub fn start(targets: Vec<DeleteTarget>, options: DeleteOptions) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let total = targets.len();
+
+        let stop = Arc::clone(&cancelled);
+        let worker_options = options.clone();
+        std::thread::spawn(move || {
+            for target in targets {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                if sender.send(delete_target(target, &worker_options)).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Self { receiver, cancelled, total, outcomes: Vec::with_capacity(total), options }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        self.outcomes.len() as f32 / self.total as f32
+    }
+
+    // Takes in whatever the worker has sent since the last frame. Returns true once
+    // it's done, whether it got through everything or was cancelled.
+    pub fn poll(&mut self) -> bool {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(outcome) => self.outcomes.push(outcome),
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => return true,
+            }
+        }
+    }
+
+    // Blocks until the worker is done, for the command line where nothing else runs
+    pub fn wait(&mut self) {
+        self.outcomes.extend(self.receiver.iter());
+    }
+}
+
+impl Drop for Deletion {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+// Files next to a file with the trigger extension that share its name and have one
+// of the supporting extensions, such as the .dll and .ini files beside a setup.exe
+pub fn associated_files(trigger_path: &Path, trigger_extension: &str, extensions: &[String]) -> Vec<PathBuf> {
+    let trigger = trigger_extension.trim().trim_start_matches('.');
+    let is_trigger = !trigger.is_empty() && trigger_path.extension()
+        .is_some_and(|ext| ext.to_string_lossy().eq_ignore_ascii_case(trigger));
+    if !is_trigger {
+        return Vec::new();
+    }
+    let (Some(base_name), Some(dir)) = (trigger_path.file_stem(), trigger_path.parent()) else {
+        return Vec::new();
+    };
+    let base_name = base_name.to_string_lossy();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let supporting_extensions: Vec<String> = extensions.iter()
+        .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .map(|ext| format!(".{}", ext))
+        .collect();
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path != trigger_path)
+        .filter(|path| {
+            let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            let file_lower = file_name.to_lowercase();
+            is_associated_name(&base_name, &file_name)
+                && supporting_extensions.iter().any(|ext| file_lower.ends_with(ext))
+        })
+        .collect()
+}
+
+// The base name has to end at a separator, so setup.exe takes setup.dll,
+// setup_x64.dll and setup-en.ini but leaves setuphelper.dll alone. Case is ignored.
+fn is_associated_name(base_name: &str, file_name: &str) -> bool {
+    file_name.to_lowercase()
+        .strip_prefix(&base_name.to_lowercase())
+        .is_some_and(|rest| rest.starts_with(['.', '_', '-']))
+}
+
+// The target goes first, so if it can't be removed its associated files stay with it
+fn delete_target(target: DeleteTarget, options: &DeleteOptions) -> DeleteOutcome {
+    let mut forced = 0;
+    let size_bytes = fs::metadata(&target.path)
+        .map(|m| match target.size_bytes {
+            _ if !m.is_dir() => m.len(),
+            Some(size_bytes) => size_bytes,
+            None => crate::scanner::path_size(&target.path),
+        })
+        .unwrap_or(0);
+    let removed = remove(target.path.clone(), size_bytes, options, &mut forced);
+
+    let mut associated = Vec::new();
+    let mut associated_failed = Vec::new();
+    if removed.is_ok() {
+        for path in target.associated {
+            let size_bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            match remove(path.clone(), size_bytes, options, &mut forced) {
+                Ok(removal) => associated.push(removal),
+                Err(error) => associated_failed.push((path, error)),
+            }
+        }
+    }
+
+    DeleteOutcome { path: target.path, removed, associated, associated_failed, forced }
+}
+
+// A read-only file that refuses to go is retried once with the flag cleared, if allowed
+fn remove(path: PathBuf, size_bytes: u64, options: &DeleteOptions, forced: &mut usize) -> Result<Removal, String> {
+    let to_trash = options.to_trash && !options.trash_directories.iter().any(|dir| path.starts_with(dir));
+    if options.dry_run {
+        return Ok(Removal { path, size_bytes, trashed: to_trash });
+    }
+
+    match remove_file(&path, to_trash, options.secure) {
+        Ok(()) => {}
+        Err(e) if options.force_read_only
+            && e.kind() == std::io::ErrorKind::PermissionDenied
+            && clear_read_only(&path) =>
+        {
+            remove_file(&path, to_trash, options.secure).map_err(|e| e.to_string())?;
+            *forced += 1;
+        }
+        Err(e) => return Err(e.to_string()),
+    }
+    Ok(Removal { path, size_bytes, trashed: to_trash })
+}
+
+// With `secure`, the contents are overwritten first and nothing is removed if
+// that fails, since the user asked for more than a plain delete
+fn remove_file(path: &Path, to_trash: bool, secure: bool) -> std::io::Result<()> {
+    if secure && !to_trash {
+        overwrite_contents(path)?;
+    }
+
+    // A dead link has nothing worth keeping in the Trash, and following it to
+    // trash it fails since the target is gone
+    let broken_link = fs::symlink_metadata(path).is_ok_and(|m| m.is_symlink()) && fs::metadata(path).is_err();
+
+    if to_trash && !broken_link {
+        trash::delete(path).map_err(|e| std::io::Error::other(e.to_string()))
+    } else if path.is_dir() {
+        // macOS bundles are scanned as one item and removed whole
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+// One pass of zeros over every byte, synced to disk. Bundles have each file inside
+// overwritten; symlinks are left alone rather than followed.
+fn overwrite_contents(path: &Path) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path)? {
+            overwrite_contents(&entry?.path())?;
+        }
+        return Ok(());
+    }
+    if !metadata.is_file() {
+        return Ok(());
+    }
+
+    let mut file = fs::OpenOptions::new().write(true).open(path)?;
+    let zeros = vec![0u8; 64 * 1024];
+    let mut remaining = metadata.len();
+    while remaining > 0 {
+        let chunk = remaining.min(zeros.len() as u64) as usize;
+        file.write_all(&zeros[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all()
+}
+
+// Clears the read-only attribute so a retry can go through. Elsewhere that bit
+// doesn't stop a delete, so there's nothing to clear.
+#[cfg(windows)]
+fn clear_read_only(path: &Path) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+    let mut permissions = metadata.permissions();
+    if !permissions.readonly() {
+        return false;
+    }
+    // On Windows this only flips the attribute, it doesn't open the file up to anyone
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(path, permissions).is_ok()
+}
+
+#[cfg(not(windows))]
+fn clear_read_only(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn associated_names_end_the_base_at_a_separator() {
+        for file_name in ["Setup.dll", "setup_x64.dll", "setup-en.ini", "SETUP.DLL"] {
+            assert!(is_associated_name("setup", file_name), "{} should go with setup", file_name);
+        }
+        for file_name in ["SetupHelper.dll", "setuphelper.dll"] {
+            assert!(!is_associated_name("setup", file_name), "{} should be left alone", file_name);
+        }
+    }
+}
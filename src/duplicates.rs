@@ -0,0 +1,55 @@
+// Finding files with identical contents among everything a scan looked at
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+
+// Groups `files` (path, size) into sets with the same contents, each sorted by path.
+// Only files that share a size with another file are hashed, which rules out most
+// of them without reading a byte. Empty files are left out, as are files that
+// can't be read.
+pub fn find_duplicates(files: Vec<(PathBuf, u64)>) -> Vec<Vec<PathBuf>> {
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for (path, size) in files {
+        if size > 0 {
+            by_size.entry(size).or_default().push(path);
+        }
+    }
+
+    let candidates: Vec<(u64, PathBuf)> = by_size
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .flat_map(|(size, paths)| paths.into_iter().map(move |path| (size, path)))
+        .collect();
+
+    let hashed: Vec<((u64, [u8; 32]), PathBuf)> = candidates
+        .into_par_iter()
+        .filter_map(|(size, path)| Some(((size, hash_file(&path).ok()?), path)))
+        .collect();
+
+    let mut by_contents: HashMap<(u64, [u8; 32]), Vec<PathBuf>> = HashMap::new();
+    for (key, path) in hashed {
+        by_contents.entry(key).or_default().push(path);
+    }
+
+    let mut groups: Vec<Vec<PathBuf>> = by_contents
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .map(|mut paths| {
+            paths.sort();
+            paths
+        })
+        .collect();
+    groups.sort();
+    groups
+}
+
+fn hash_file(path: &Path) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
@@ -0,0 +1,60 @@
+// Audit log of everything the app has removed, kept next to the settings file
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Clone)]
+pub struct Removal {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub trashed: bool,
+}
+
+// e.g. ~/.config/pinnaclesort/history.log on Linux
+fn log_path() -> Option<PathBuf> {
+    Some(crate::settings::config_dir()?.join("history.log"))
+}
+
+// One tab-separated line per file: local time, how it went, size in bytes, path.
+// Once the log passes `max_bytes` it becomes history.log.1 (replacing the previous
+// one) and a fresh log is started, so at most twice that is kept on disk.
+pub fn append(removals: &[Removal], max_bytes: u64) -> Result<(), String> {
+    if removals.is_empty() {
+        return Ok(());
+    }
+
+    let path = log_path().ok_or_else(|| "no config directory for this user".to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    if fs::metadata(&path).is_ok_and(|metadata| metadata.len() >= max_bytes) {
+        fs::rename(&path, path.with_extension("log.1")).map_err(|e| e.to_string())?;
+    }
+
+    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+    let mut lines = String::new();
+    for removal in removals {
+        let method = if removal.trashed { "trashed" } else { "deleted" };
+        lines.push_str(&format!(
+            "{}\t{}\t{}\t{}\n", timestamp, method, removal.size_bytes, removal.path.display()));
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| e.to_string())?;
+    file.write_all(lines.as_bytes()).map_err(|e| e.to_string())
+}
+
+// The log's lines, newest first. No log yet just means nothing has been removed.
+pub fn load() -> Result<Vec<String>, String> {
+    let path = log_path().ok_or_else(|| "no config directory for this user".to_string())?;
+    match fs::read_to_string(&path) {
+        Ok(contents) => Ok(contents.lines().rev().map(str::to_string).collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
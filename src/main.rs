@@ -1,123 +1,636 @@
 #![cfg_attr(target_os = "windows", windows_subsystem = "windows")]
 
+mod archive;
+mod cleanignore;
+mod cli;
+mod deletion;
+mod duplicates;
+mod history;
+mod report;
+mod sandbox;
+mod scan_cache;
+mod scanner;
+mod schedule;
+mod settings;
+mod thumbnails;
+mod trash_bin;
+mod tray;
+mod usage;
+mod volumes;
+mod watcher;
+
 use std::fs;
 use eframe::egui;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::rc::Rc;
+use std::sync::Arc;
+use scanner::{FilterCategory, FilterRules, ScanEvent, ScanOptions, ScanProgress, ScanResult, ScanStats, TimeBasis, TimeCombiner, TimeUnit};
+use settings::Settings;
+use trash_bin::TrashVolume;
+use volumes::{DriveSpace, ExternalVolumes, VolumeKind};
 
+// Rendered from assets/icon.svg. The window takes a single image, which each
+// platform scales down for the taskbar and title bar, so only the largest is kept.
 fn load_icon() -> egui::IconData {
-    // Create a simple 256x256 icon programmatically
-    let size = 256;
-    let mut pixels = vec![0u8; size * size * 4]; // RGBA
-    
-    // Fill with blue background
-    for y in 0..size {
-        for x in 0..size {
-            let idx = (y * size + x) * 4;
-            let dx = x as f32 - 128.0;
-            let dy = y as f32 - 128.0;
-            let dist = (dx * dx + dy * dy).sqrt();
-            
-            // Draw circular background
-            if dist < 120.0 {
-                pixels[idx] = 74;      // R - Blue background
-                pixels[idx + 1] = 144; // G
-                pixels[idx + 2] = 226; // B
-                pixels[idx + 3] = 255; // A
-                
-                // Draw folder shape (simplified)
-                if y >= 90 && y <= 170 && x >= 60 && x <= 196 {
-                    pixels[idx] = 255;     // R - Orange folder
-                    pixels[idx + 1] = 165; // G
-                    pixels[idx + 2] = 0;   // B
-                }
-                
-                // Draw folder tab
-                if y >= 70 && y <= 90 && x >= 110 && x <= 196 {
-                    pixels[idx] = 255;     // R - Yellow tab
-                    pixels[idx + 1] = 215; // G
-                    pixels[idx + 2] = 0;   // B
-                }
-                
-                // Add checkmark (simplified - just draw it as colored pixels)
-                if (x >= 75 && x <= 85 && y >= 130 && y <= 140) ||
-                   (x >= 85 && x <= 105 && y >= 110 && y <= 130 && 
-                    ((x as i32 - 85).abs() + (y as i32 - 120).abs()) < 10) {
-                    pixels[idx] = 0;       // R - Green checkmark
-                    pixels[idx + 1] = 255; // G
-                    pixels[idx + 2] = 0;   // B
-                }
-            } else {
-                pixels[idx + 3] = 0; // Transparent outside circle
-            }
-        }
-    }
+    let Ok(image) = image::load_from_memory_with_format(include_bytes!("../assets/icon.png"), image::ImageFormat::Png) else {
+        return egui::IconData::default();
+    };
+    let image = image.into_rgba8();
     
     egui::IconData {
-        rgba: pixels,
-        width: size as u32,
-        height: size as u32,
+        width: image.width(),
+        height: image.height(),
+        rgba: image.into_raw(),
+    }
+}
+
+// Brings the window back from the tray or the taskbar, from any thread
+fn show_window(ctx: &egui::Context) {
+    ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+    ctx.request_repaint();
+}
+
+fn main() -> std::process::ExitCode {
+    let args = <cli::Args as clap::Parser>::parse();
+    if args.headless {
+        return cli::run(args);
+    }
+    
+    match run_gui() {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::ExitCode::FAILURE
+        }
     }
 }
 
-fn main() -> Result<(), eframe::Error> {
+fn run_gui() -> Result<(), eframe::Error> {
     let icon = load_icon();
+    let settings = settings::load().unwrap_or_default();
+    
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size(settings.window_size.unwrap_or([600.0, 700.0]))
+        .with_icon(std::sync::Arc::new(icon))
+        .with_drag_and_drop(true);
+    if let Some(position) = settings.window_position {
+        viewport = viewport.with_position(position);
+    }
     
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([600.0, 700.0])
-            .with_icon(std::sync::Arc::new(icon)),
+        viewport,
         ..Default::default()
     };
     
     eframe::run_native(
         "PinnacleSort - File Cleaner",
         options,
-        Box::new(|_cc| Ok(Box::new(FileCleanerApp::default()))),
+        Box::new(|_cc| Ok(Box::new(FileCleanerApp::new(settings)))),
     )
 }
 
 struct FileCleanerApp {
-    time_limit_days: u64,
+    time_limit: u64,
+    time_unit: TimeUnit,
+    time_basis: TimeBasis,
+    time_combiner: TimeCombiner,
     downloads_enabled: bool,
     documents_enabled: bool,
     desktop_enabled: bool,
+    pictures_enabled: bool,
+    videos_enabled: bool,
+    music_enabled: bool,
+    trash_scan_enabled: bool,
     custom_directories: Vec<String>,
+    exclude_patterns: Vec<String>,
+    respect_gitignore: bool,
+    protected_paths: Vec<String>,
+    never_delete: HashSet<String>,
+    never_delete_patterns: Vec<String>,
+    confirmed_external_directories: HashSet<String>,
+    // Scan targets on network or removable drives, waiting for the user to confirm
+    external_scan_warning: Option<Vec<(String, VolumeKind)>>,
+    // Results left from the last session, waiting for the user to restore or discard them
+    pending_restore: Option<scan_cache::ScanCache>,
+    associated_trigger_extension: String,
+    associated_extensions: Vec<String>,
     new_directory: String,
+    directory_error: Option<String>,
     scan_results: Vec<ScanResult>,
     is_scanning: bool,
     status_message: String,
     smart_filter_enabled: bool,
-    top_panel_height: f32,
+    filter_rules: FilterRules,
+    junk_patterns: Vec<String>,
+    trash_report_enabled: bool,
+    trash_volumes: Vec<TrashVolume>,
+    trash_error: Option<String>,
+    history_max_kb: u64,
+    scheduled_scan_days: Option<u64>,
+    last_scheduled_scan: Option<u64>,
+    scan_schedule: Option<schedule::ScanSchedule>,
+    // Kept while scheduled scans are on, so closing the window only hides it
+    tray: Option<tray::Tray>,
+    // What the last scheduled scan found, until it's shown or dismissed
+    scheduled_results: Option<schedule::ScheduledResults>,
+    // The deletion history, newest first, while its window is open
+    history_lines: Option<Vec<String>>,
+    about_open: bool,
+    confirm_empty_trash_open: bool,
+    // Used and free space of the drives holding the scan targets, as of the last
+    // scan or removal
+    drive_space: Vec<DriveSpace>,
+    permanent_delete: bool,
+    secure_delete: bool,
+    force_read_only: bool,
+    dry_run: bool,
+    remove_emptied_folders: bool,
+    dry_run_log: Vec<PathBuf>,
+    last_deleted: Vec<PathBuf>,
+    confirm_delete_open: bool,
+    confirm_delete_acknowledged: bool,
+    confirm_delete_scope: DeleteScope,
+    // Associated files the pending delete will also remove, found when the dialog
+    // opens and again whenever the selection changes under it
+    confirm_associated: Vec<PathBuf>,
+    // The space the pending delete frees, and the selection (scope, count, bytes,
+    // marked folders) that was worked out for
+    confirm_free_bytes: u64,
+    confirm_estimate_for: Option<(DeleteScope, usize, u64, BTreeSet<PathBuf>)>,
+    last_deleted_at: Option<std::time::Instant>,
+    sandbox_mode: bool,
+    sandbox_root: Option<PathBuf>,
+    usage_signal_enabled: bool,
+    frequent_use_count: u64,
+    selected_tab: Tab,
+    quick_clean: Option<QuickClean>,
+    large_dir_threshold: usize,
+    follow_symlinks: bool,
+    include_hidden: bool,
+    max_depth: Option<usize>,
+    protect_modified_days: Option<u64>,
+    duplicates_enabled: bool,
+    skipped_large_dirs: Vec<(PathBuf, usize)>,
+    scan_errors: Vec<(String, String)>,
+    // Debug aid: every file the smart filter dropped, when listing them is switched on
+    list_filtered: bool,
+    filtered_paths: Vec<(PathBuf, FilterCategory)>,
+    last_stats: Option<ScanStats>,
+    large_dirs_allowed: HashSet<PathBuf>,
+    scan_roots: Vec<PathBuf>,
+    archive_destination: String,
+    zip_remove_originals: bool,
+    home_directory: String,
+    archive_errors: Vec<(String, String)>,
+    zipping: Option<archive::Zipping>,
+    archiving: Option<archive::Archiving>,
+    preset: Preset,
+    preselect_results: bool,
+    incomplete_downloads_enabled: bool,
+    incomplete_download_days: u64,
+    min_size_mb: Option<u64>,
+    max_size_mb: Option<u64>,
+    include_extensions: Vec<String>,
+    date_display: DateDisplay,
+    date_format: String,
+    window_size: Option<[f32; 2]>,
+    window_position: Option<[f32; 2]>,
+    expanded_folders: HashSet<String>,
+    auto_expand_below: usize,
+    sort_key: SortKey,
+    sort_descending: bool,
+    search_query: String,
+    // Thresholds for the "select older than" and "select larger than" quick actions
+    quick_select_days: u64,
+    quick_select_mb: u64,
+    view_mode: ViewMode,
+    selection_filter: SelectionFilter,
+    result_tree: Option<Rc<ResultTree>>,
+    // Selection from before a rescan, by path, carried over as results come back
+    previous_selection: HashMap<PathBuf, bool>,
+    // Where the results list was scrolled to when the last scan started, restored once it ends
+    results_scroll_offset: f32,
+    restore_scroll: Option<f32>,
+    // Set when results have streamed in that the tree doesn't show yet
+    tree_stale_since: Option<std::time::Instant>,
+    // Measured height of a result row, for skipping the rows that are scrolled out of view
+    file_row_height: f32,
+    scan_receiver: Option<Receiver<ScanEvent>>,
+    live_enabled: bool,
+    live_watcher: Option<watcher::LiveWatcher>,
+    // Last change the watcher reported that hasn't been rescanned yet
+    live_changed_at: Option<std::time::Instant>,
+    scan_kind: ScanKind,
+    scan_progress: Arc<ScanProgress>,
+    // Folders from the tree to remove whole, everything in them included
+    marked_folders: BTreeSet<PathBuf>,
+    // The removal running on the worker thread, if any
+    deletion: Option<deletion::Deletion>,
+    // Previews of image results, decoded when first hovered
+    thumbnails: thumbnails::Thumbnails,
+    // The last result whose checkbox was clicked, where a shift-click range starts.
+    // Kept by path since the results are reordered and removed under it.
+    last_clicked: Option<PathBuf>,
+    saved_settings: Settings,
+}
+
+// The folder structure of the results. It only depends on which files were found
+// and how they're sorted and filtered, so it's kept until one of those changes;
+// ticking checkboxes doesn't touch it.
+struct ResultTree {
+    tree: HashMap<String, Vec<String>>,
+    file_map: HashMap<String, Vec<usize>>,
+    incomplete_downloads: Vec<usize>,
+    roots: Vec<String>,
+    // Every matching result in display order, and the same grouped by extension
+    flat: Vec<usize>,
+    by_type: Vec<(String, Vec<usize>)>,
+    // Positions in `by_type` with each group's total size, biggest first
+    type_sizes: Vec<(usize, u64)>,
+    duplicate_groups: Vec<Vec<usize>>,
+    // The biggest and the longest-unused matching results, for the top files report
+    largest: Vec<usize>,
+    oldest: Vec<usize>,
+    // Where the search matched in each result's path, as char positions
+    highlights: HashMap<usize, Vec<usize>>,
+    sort_key: SortKey,
+    sort_descending: bool,
+    search_query: String,
+    selection_filter: SelectionFilter,
+}
+
+// What the running scan was started for, which decides what happens when it ends
+enum ScanKind {
+    Full,
+    QuickClean,
+    Directory { path: PathBuf, results_before: usize },
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+enum Preset {
+    Conservative,
+    Balanced,
+    Aggressive,
+    Junk,
+}
+
+impl Preset {
+    const ALL: [Preset; 4] = [Preset::Conservative, Preset::Balanced, Preset::Aggressive, Preset::Junk];
+    
+    fn label(self) -> &'static str {
+        match self {
+            Preset::Conservative => "🛡️ Conservative",
+            Preset::Balanced => "⚖️ Balanced",
+            Preset::Aggressive => "🔥 Aggressive",
+            Preset::Junk => "🧹 Junk",
+        }
+    }
+    
+    fn description(self) -> &'static str {
+        match self {
+            Preset::Conservative => "180 days, nothing pre-selected, frequently used files kept",
+            Preset::Balanced => "14 days, everything found pre-selected",
+            Preset::Aggressive => "7 days, smart filter off, large directories included",
+            Preset::Junk => "30 days, only files on the junk list below, all pre-selected",
+        }
+    }
+}
+
+// How a file's last use is shown in the results
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+enum DateDisplay {
+    Relative,
+    Absolute,
+}
+
+impl DateDisplay {
+    const ALL: [DateDisplay; 2] = [DateDisplay::Relative, DateDisplay::Absolute];
+    
+    fn label(self) -> &'static str {
+        match self {
+            DateDisplay::Relative => "How long ago",
+            DateDisplay::Absolute => "Date",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tab {
+    Settings,
+    Results,
+    Log,
+}
+
+impl Tab {
+    const ALL: [Tab; 3] = [Tab::Settings, Tab::Results, Tab::Log];
+    
+    fn label(self) -> &'static str {
+        match self {
+            Tab::Settings => "⚙️ Settings",
+            Tab::Results => "📊 Results",
+            Tab::Log => "📋 Log",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ViewMode {
+    Tree,
+    ByType,
+    Flat,
+}
+
+impl ViewMode {
+    const ALL: [ViewMode; 3] = [ViewMode::Tree, ViewMode::ByType, ViewMode::Flat];
+    
+    fn label(self) -> &'static str {
+        match self {
+            ViewMode::Tree => "🌳 Tree",
+            ViewMode::ByType => "🏷️ By Type",
+            ViewMode::Flat => "📃 Flat",
+        }
+    }
+}
+
+// Which results the list shows. Applied when the tree is built, so a file unticked
+// while looking at Selected stays in view until the filter changes, instead of
+// vanishing from under the cursor.
+#[derive(Clone, Copy, PartialEq)]
+enum SelectionFilter {
+    All,
+    Selected,
+    Unselected,
+}
+
+impl SelectionFilter {
+    const ALL: [SelectionFilter; 3] = [SelectionFilter::All, SelectionFilter::Selected, SelectionFilter::Unselected];
+    
+    fn label(self) -> &'static str {
+        match self {
+            SelectionFilter::All => "All",
+            SelectionFilter::Selected => "🗑️ Selected",
+            SelectionFilter::Unselected => "📄 Unselected",
+        }
+    }
+    
+    fn matches(self, result: &ScanResult) -> bool {
+        match self {
+            SelectionFilter::All => true,
+            SelectionFilter::Selected => result.should_delete,
+            SelectionFilter::Unselected => !result.should_delete,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SortKey {
+    Name,
+    Size,
+    DaysSinceAccess,
+    Path,
 }
 
-#[derive(Clone)]
-struct ScanResult {
-    file_path: String,
-    file_name: String,
-    should_delete: bool,
-    days_since_access: u64,
+impl SortKey {
+    const ALL: [SortKey; 4] = [SortKey::Name, SortKey::Size, SortKey::DaysSinceAccess, SortKey::Path];
+    
+    fn label(self) -> &'static str {
+        match self {
+            SortKey::Name => "Name",
+            SortKey::Size => "Size",
+            SortKey::DaysSinceAccess => "Days Since Access",
+            SortKey::Path => "Path",
+        }
+    }
+}
+
+// Opinionated, safe defaults for the one-button flow
+const QUICK_CLEAN_DAYS: u64 = 60;
+
+// Deletes at or above either limit need an extra acknowledgement
+const LARGE_DELETE_FILES: usize = 100;
+const LARGE_DELETE_BYTES: u64 = 1024 * 1024 * 1024;
+
+// How long the Undo button stays up after files are moved to the Trash
+const UNDO_WINDOW: std::time::Duration = std::time::Duration::from_secs(10);
+
+// Rebuilding the tree sorts every result, so while a scan streams them in it's
+// refreshed at most this often instead of on every frame
+const TREE_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+// Live mode waits for changes to settle for this long before rescanning, so a
+// download or an unzip in progress only causes one rescan
+const LIVE_DEBOUNCE: std::time::Duration = std::time::Duration::from_secs(2);
+
+#[derive(Clone, Copy)]
+enum QuickClean {
+    Review { before_bytes: u64, found_bytes: u64 },
+    Deleting { before_bytes: u64 },
+    Done { before_bytes: u64, after_bytes: u64 },
+}
+
+// Which results a delete is for. Only a delete of the selection takes the marked
+// folders along.
+#[derive(Clone, PartialEq)]
+enum DeleteScope {
+    Selected,
+    // The files the last delete couldn't remove, selected or not
+    Failed,
+    // The originals of a zip that was just written
+    Zipped(HashSet<PathBuf>),
+}
+
+impl DeleteScope {
+    fn includes(&self, result: &ScanResult) -> bool {
+        match self {
+            DeleteScope::Selected => result.should_delete,
+            DeleteScope::Failed => result.delete_error.is_some() && !result.kept,
+            DeleteScope::Zipped(paths) => paths.contains(&result.file_path) && !result.kept,
+        }
+    }
+    
+    fn takes_marked_folders(&self) -> bool {
+        *self == DeleteScope::Selected
+    }
 }
 
 impl Default for FileCleanerApp {
     fn default() -> Self {
+        let defaults = Settings::default();
         Self {
-            time_limit_days: 14,
+            time_limit: defaults.time_limit,
+            time_unit: defaults.time_unit,
+            time_basis: TimeBasis::Accessed,
+            time_combiner: TimeCombiner::Single,
             downloads_enabled: true,
             documents_enabled: true,
             desktop_enabled: true,
+            pictures_enabled: false,
+            videos_enabled: false,
+            music_enabled: false,
+            trash_scan_enabled: false,
             custom_directories: Vec::new(),
+            exclude_patterns: Vec::new(),
+            respect_gitignore: false,
+            protected_paths: defaults.protected_paths,
+            never_delete: HashSet::new(),
+            never_delete_patterns: Vec::new(),
+            confirmed_external_directories: HashSet::new(),
+            external_scan_warning: None,
+            pending_restore: None,
+            associated_trigger_extension: defaults.associated_trigger_extension,
+            associated_extensions: defaults.associated_extensions,
             new_directory: String::new(),
+            directory_error: None,
             scan_results: Vec::new(),
             is_scanning: false,
             status_message: String::new(),
             smart_filter_enabled: true,
-            top_panel_height: 200.0, // Smaller for settings only
+            filter_rules: FilterRules::default(),
+            junk_patterns: scanner::default_junk_patterns(),
+            trash_report_enabled: false,
+            trash_volumes: Vec::new(),
+            trash_error: None,
+            history_max_kb: defaults.history_max_kb,
+            scheduled_scan_days: defaults.scheduled_scan_days,
+            last_scheduled_scan: defaults.last_scheduled_scan,
+            scan_schedule: None,
+            tray: None,
+            scheduled_results: None,
+            history_lines: None,
+            about_open: false,
+            confirm_empty_trash_open: false,
+            drive_space: Vec::new(),
+            permanent_delete: false,
+            secure_delete: false,
+            force_read_only: false,
+            dry_run: false,
+            remove_emptied_folders: false,
+            dry_run_log: Vec::new(),
+            last_deleted: Vec::new(),
+            confirm_delete_open: false,
+            confirm_delete_acknowledged: false,
+            confirm_delete_scope: DeleteScope::Selected,
+            confirm_associated: Vec::new(),
+            confirm_free_bytes: 0,
+            confirm_estimate_for: None,
+            last_deleted_at: None,
+            sandbox_mode: false,
+            sandbox_root: None,
+            usage_signal_enabled: false,
+            frequent_use_count: 10,
+            selected_tab: Tab::Settings,
+            quick_clean: None,
+            large_dir_threshold: 10_000,
+            follow_symlinks: true,
+            include_hidden: false,
+            max_depth: None,
+            protect_modified_days: None,
+            duplicates_enabled: false,
+            skipped_large_dirs: Vec::new(),
+            scan_errors: Vec::new(),
+            list_filtered: false,
+            filtered_paths: Vec::new(),
+            last_stats: None,
+            large_dirs_allowed: HashSet::new(),
+            scan_roots: Vec::new(),
+            archive_destination: String::new(),
+            zip_remove_originals: false,
+            home_directory: String::new(),
+            archive_errors: Vec::new(),
+            zipping: None,
+            archiving: None,
+            preset: Preset::Balanced,
+            preselect_results: true,
+            incomplete_downloads_enabled: true,
+            incomplete_download_days: 1,
+            min_size_mb: None,
+            max_size_mb: None,
+            include_extensions: Vec::new(),
+            date_display: defaults.date_display,
+            date_format: defaults.date_format.clone(),
+            window_size: None,
+            window_position: None,
+            expanded_folders: HashSet::new(),
+            auto_expand_below: 25,
+            sort_key: SortKey::Name,
+            sort_descending: false,
+            search_query: String::new(),
+            quick_select_days: 180,
+            quick_select_mb: 100,
+            view_mode: ViewMode::Tree,
+            selection_filter: SelectionFilter::All,
+            result_tree: None,
+            previous_selection: HashMap::new(),
+            results_scroll_offset: 0.0,
+            restore_scroll: None,
+            tree_stale_since: None,
+            file_row_height: 32.0,
+            scan_receiver: None,
+            live_enabled: false,
+            live_watcher: None,
+            live_changed_at: None,
+            scan_kind: ScanKind::Full,
+            scan_progress: Arc::default(),
+            marked_folders: BTreeSet::new(),
+            deletion: None,
+            thumbnails: thumbnails::Thumbnails::default(),
+            last_clicked: None,
+            saved_settings: Settings::default(),
         }
     }
 }
 
 impl eframe::App for FileCleanerApp {
+    // Stop the watcher's thread, and any deletion or archive after the file it's on, before the process goes away
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.stop_live_watcher();
+        self.deletion = None;
+        self.archiving = None;
+        self.save_scan_cache();
+    }
+    
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // With the tray icon up, closing the window only hides it so scheduled scans
+        // keep running; the tray menu's Quit really closes it
+        if self.tray.as_ref().is_some_and(|tray| !tray.quitting())
+            && ctx.input(|i| i.viewport().close_requested())
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        }
+        
+        if self.is_scanning {
+            self.poll_scan();
+            // Keep draining the channel even when there's no input to trigger a frame
+            ctx.request_repaint_after(std::time::Duration::from_millis(50));
+        }
+        if self.deletion.is_some() {
+            self.poll_deletion();
+            ctx.request_repaint_after(std::time::Duration::from_millis(50));
+        }
+        if self.zipping.is_some() {
+            self.poll_zipping();
+            ctx.request_repaint_after(std::time::Duration::from_millis(50));
+        }
+        if self.archiving.is_some() {
+            self.poll_archiving();
+            ctx.request_repaint_after(std::time::Duration::from_millis(50));
+        }
+        
+        self.track_window_geometry(ctx);
+        // Turning a directory on or off changes what Live mode should be watching
+        if self.save_settings_if_changed() && self.live_watcher.is_some() {
+            self.start_live_watcher(ctx);
+        }
+        self.poll_live_watcher(ctx);
+        self.sync_schedule(ctx);
+        self.handle_dropped_folders(ctx);
+        self.handle_shortcuts(ctx);
+        
         // Fixed title header at the top
         egui::TopBottomPanel::top("title_header")
             .resizable(false)
@@ -136,177 +649,49 @@ impl eframe::App for FileCleanerApp {
                             .size(12.0)
                             .color(egui::Color32::from_rgb(200, 200, 255)));
                     });
-                });
-            });
-        
-        egui::CentralPanel::default().show(ctx, |ui| {
-            let available_height = ui.available_height();
-            
-            // Top panel for settings (without title now)
-            egui::TopBottomPanel::top("settings_panel")
-                .exact_height(self.top_panel_height)
-                .resizable(false)
-                .show_inside(ui, |ui| {
-                    egui::ScrollArea::vertical()
-                        .auto_shrink([false, false])
-                        .show(ui, |ui| {
-            ui.add_space(8.0);
-            
-            // Time limit section with better styling
-            let settings_frame = egui::Frame::none()
-                .fill(egui::Color32::from_rgb(250, 250, 250))
-                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
-                .inner_margin(egui::Margin::same(10.0))
-                .rounding(egui::Rounding::same(4.0));
-            
-            settings_frame.show(ui, |ui| {
-                ui.label(egui::RichText::new("⏰ Time Threshold")
-                    .size(14.0)
-                    .strong()
-                    .color(egui::Color32::BLACK));
-                ui.add_space(6.0);
-                ui.horizontal(|ui| {
-                    ui.label(egui::RichText::new("Delete files not accessed in:")
-                        .size(12.0)
-                        .color(egui::Color32::from_rgb(80, 80, 80)));
-                    ui.add(egui::Slider::new(&mut self.time_limit_days, 1..=365)
-                        .suffix(" days"));
-                });
-            });
-            ui.add_space(8.0);
-            
-            // Directory selection
-            let dir_frame = egui::Frame::none()
-                .fill(egui::Color32::from_rgb(250, 250, 250))
-                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
-                .inner_margin(egui::Margin::same(10.0))
-                .rounding(egui::Rounding::same(4.0));
-            
-            dir_frame.show(ui, |ui| {
-                ui.label(egui::RichText::new("📁 Directories to Search")
-                    .size(14.0)
-                    .strong()
-                    .color(egui::Color32::BLACK));
-                ui.add_space(6.0);
-                ui.checkbox(&mut self.downloads_enabled, 
-                    egui::RichText::new("📥 Downloads").size(12.0).color(egui::Color32::BLACK));
-                ui.checkbox(&mut self.documents_enabled, 
-                    egui::RichText::new("📝 Documents").size(12.0).color(egui::Color32::BLACK));
-                ui.checkbox(&mut self.desktop_enabled, 
-                    egui::RichText::new("🖥️ Desktop").size(12.0).color(egui::Color32::BLACK));
-            });
-            ui.add_space(8.0);
-            
-            // Custom directories below
-            let custom_frame = egui::Frame::none()
-                .fill(egui::Color32::from_rgb(250, 250, 250))
-                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
-                .inner_margin(egui::Margin::same(10.0))
-                .rounding(egui::Rounding::same(4.0));
-            
-            custom_frame.show(ui, |ui| {
-                ui.label(egui::RichText::new("➕ Custom Directories")
-                    .size(14.0)
-                    .strong()
-                    .color(egui::Color32::BLACK));
-                ui.add_space(6.0);
-                ui.horizontal(|ui| {
-                    ui.label(egui::RichText::new("Path:").size(12.0).color(egui::Color32::from_rgb(80, 80, 80)));
-                    ui.text_edit_singleline(&mut self.new_directory);
-                    
-                    let add_btn = egui::Button::new(
-                        egui::RichText::new("Add").size(12.0).color(egui::Color32::WHITE)
-                    )
-                    .fill(egui::Color32::from_rgb(76, 175, 80))
-                    .rounding(egui::Rounding::same(3.0))
-                    .min_size(egui::vec2(50.0, 24.0));
                     
-                    if ui.add(add_btn).clicked() && !self.new_directory.is_empty() {
-                        self.custom_directories.push(self.new_directory.clone());
-                        self.new_directory.clear();
+                    // Tucked into the corner so the title stays centered
+                    let corner = egui::Rect::from_min_size(
+                        egui::pos2(ui.max_rect().right() - 28.0, ui.max_rect().top()),
+                        egui::vec2(28.0, 28.0));
+                    let about_btn = egui::Button::new(egui::RichText::new("ℹ️").size(16.0).color(egui::Color32::WHITE))
+                        .frame(false);
+                    if ui.put(corner, about_btn).on_hover_text("About PinnacleSort").clicked() {
+                        self.about_open = true;
                     }
                 });
-                
-                // Display custom directories
-                if !self.custom_directories.is_empty() {
-                    ui.add_space(6.0);
-                }
-                let mut to_remove = None;
-                for (idx, dir) in self.custom_directories.iter().enumerate() {
-                    ui.horizontal(|ui| {
-                        ui.label(egui::RichText::new(format!("📂 {}", dir))
-                            .size(11.0)
-                            .color(egui::Color32::from_rgb(80, 80, 80)));
-                        
-                        let remove_btn = egui::Button::new(
-                            egui::RichText::new("✕").size(11.0).color(egui::Color32::WHITE)
-                        )
-                        .fill(egui::Color32::from_rgb(244, 67, 54))
-                        .rounding(egui::Rounding::same(2.0))
-                        .min_size(egui::vec2(24.0, 18.0));
-                        
-                        if ui.add(remove_btn).clicked() {
-                            to_remove = Some(idx);
-                        }
-                    });
-                }
-                if let Some(idx) = to_remove {
-                    self.custom_directories.remove(idx);
-                }
-            });
-            ui.add_space(8.0);
-            
-            // Smart filter option
-            let smart_frame = egui::Frame::none()
-                .fill(egui::Color32::from_rgb(250, 250, 250))
-                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
-                .inner_margin(egui::Margin::same(10.0))
-                .rounding(egui::Rounding::same(4.0));
-            
-            smart_frame.show(ui, |ui| {
-                ui.checkbox(&mut self.smart_filter_enabled, 
-                    egui::RichText::new("🧠 Smart Filter (exclude binary/system files)")
-                        .size(12.0)
-                        .color(egui::Color32::BLACK));
             });
-            ui.add_space(8.0);
-                    });  // Close ScrollArea
-            });  // Close TopBottomPanel
-            
-            // Resizable divider - make it more visible
-            let divider_response = ui.allocate_ui_with_layout(
-                egui::vec2(ui.available_width(), 8.0),
-                egui::Layout::top_down(egui::Align::Center),
-                |ui| {
-                    ui.add_space(2.0);
-                    // Draw a thicker, more visible separator
-                    let (rect, _) = ui.allocate_exact_size(
-                        egui::vec2(ui.available_width(), 4.0),
-                        egui::Sense::hover()
-                    );
-                    ui.painter().rect_filled(
-                        rect,
-                        0.0,
-                        egui::Color32::from_rgb(120, 120, 120)
-                    );
-                    ui.add_space(2.0);
-                }
-            ).response;
-            
-            // Handle dragging to resize
-            if divider_response.dragged() {
-                if let Some(pointer_pos) = ctx.pointer_interact_pos() {
-                    // Allow dragging but default to half the available height
-                    self.top_panel_height = pointer_pos.y.clamp(100.0, available_height - 100.0);
-                }
-            }
-            
-            // Change cursor when hovering over divider
-            if divider_response.hovered() {
-                ctx.set_cursor_icon(egui::CursorIcon::ResizeVertical);
-            }
-            
-            // Scan button OUTSIDE the top panel - always visible
+        
+        if self.confirm_empty_trash_open {
+            self.render_empty_trash_dialog(ctx);
+        }
+        
+        if self.history_lines.is_some() {
+            self.render_history_window(ctx);
+        }
+        
+        if self.about_open {
+            self.render_about_window(ctx);
+        }
+        
+        if self.quick_clean.is_some() {
+            self.render_quick_clean_dialog(ctx);
+        }
+        
+        if self.confirm_delete_open {
+            self.render_confirm_delete_dialog(ctx);
+        }
+        
+        if self.external_scan_warning.is_some() {
+            self.render_external_scan_dialog(ctx);
+        }
+        
+        if self.pending_restore.is_some() {
+            self.render_restore_dialog(ctx);
+        }
+        
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // Scan controls stay above the tabs, so a scan can be started from any of them
             ui.add_space(8.0);
             ui.horizontal(|ui| {
                 ui.add_space(4.0);
@@ -319,10 +704,62 @@ impl eframe::App for FileCleanerApp {
                 .rounding(egui::Rounding::same(4.0))
                 .min_size(egui::vec2(180.0, 32.0));
                 
-                if ui.add(scan_btn).clicked() && !self.is_scanning {
+                if self.is_scanning {
+                    let cancel_btn = egui::Button::new(
+                        egui::RichText::new("⏹ Cancel")
+                            .size(14.0)
+                            .color(egui::Color32::WHITE)
+                    )
+                    .fill(egui::Color32::from_rgb(244, 67, 54))
+                    .rounding(egui::Rounding::same(4.0))
+                    .min_size(egui::vec2(180.0, 32.0));
+                    
+                    let cancelling = self.scan_progress.is_cancelled();
+                    if ui.add_enabled(!cancelling, cancel_btn).on_hover_text("Esc").clicked() {
+                        self.scan_progress.cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+                    }
+                } else if ui.add_enabled(!self.is_busy(), scan_btn)
+                    .on_hover_text("Enter")
+                    .on_disabled_hover_text("Wait for the files being deleted, zipped or archived")
+                    .clicked()
+                {
                     self.scan_files();
                 }
                 
+                let quick_clean_btn = egui::Button::new(
+                    egui::RichText::new("✨ Quick Clean")
+                        .size(14.0)
+                        .color(egui::Color32::WHITE)
+                )
+                .fill(egui::Color32::from_rgb(76, 175, 80))
+                .rounding(egui::Rounding::same(4.0))
+                .min_size(egui::vec2(130.0, 32.0));
+                
+                if ui.add_enabled(!self.is_scanning && !self.is_busy(), quick_clean_btn)
+                    .on_hover_text(format!(
+                        "Find files in Downloads you haven't opened in {} days and move them to the Trash",
+                        QUICK_CLEAN_DAYS))
+                    .clicked()
+                {
+                    self.start_quick_clean();
+                }
+                
+                if ui.checkbox(&mut self.live_enabled, "📡 Live")
+                    .on_hover_text("Rescan automatically when files in the scanned folders change")
+                    .changed()
+                {
+                    if self.live_enabled {
+                        self.start_live_watcher(ui.ctx());
+                    } else {
+                        self.stop_live_watcher();
+                    }
+                }
+                
+                if self.is_scanning {
+                    ui.add_space(12.0);
+                    self.render_scan_progress(ui);
+                }
+                
                 // Status message inline with scan button
                 if !self.status_message.is_empty() {
                     ui.add_space(12.0);
@@ -330,158 +767,2652 @@ impl eframe::App for FileCleanerApp {
                         .size(12.0)
                         .color(egui::Color32::from_rgb(46, 125, 50)));
                 }
+                
+                self.render_undo_button(ui);
             });
+            self.render_filter_tally(ui);
             
             ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                for tab in Tab::ALL {
+                    let label = match tab {
+                        Tab::Log if self.log_entry_count() > 0 => format!("{} ({})", tab.label(), self.log_entry_count()),
+                        _ => tab.label().to_string(),
+                    };
+                    ui.selectable_value(&mut self.selected_tab, tab, egui::RichText::new(label).size(14.0));
+                }
+            });
+            ui.separator();
             
-            // Bottom panel for results
-            egui::CentralPanel::default().show_inside(ui, |ui| {
-            // Results section
-            if !self.scan_results.is_empty() {
-                let selected_count = self.scan_results.iter().filter(|r| r.should_delete).count();
-                
-                // Compact heading with background
-                let header_frame = egui::Frame::none()
-                    .fill(egui::Color32::from_rgb(245, 245, 245))
-                    .inner_margin(egui::Margin::symmetric(8.0, 6.0))
-                    .rounding(egui::Rounding::same(0.0));
-                
-                header_frame.show(ui, |ui| {
-                    ui.horizontal(|ui| {
-                        ui.label(egui::RichText::new(
-                            format!("📊 {} files  •  {} selected", 
-                                self.scan_results.len(), selected_count)
-                        ).size(13.0).strong());
-                        
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            if selected_count > 0 {
-                                let delete_btn = egui::Button::new(
-                                    egui::RichText::new(format!("🗑️ Delete {}", selected_count))
-                                        .size(12.0)
-                                        .color(egui::Color32::WHITE)
-                                )
-                                .fill(egui::Color32::from_rgb(244, 67, 54))
-                                .rounding(egui::Rounding::same(3.0))
-                                .min_size(egui::vec2(90.0, 24.0));
-                                
-                                if ui.add(delete_btn).clicked() {
-                                    self.delete_files();
-                                }
-                                ui.add_space(4.0);
-                            }
-                            
-                            let deselect_all_btn = egui::Button::new(
-                                egui::RichText::new("✗ Deselect").size(12.0).color(egui::Color32::WHITE)
-                            )
-                            .fill(egui::Color32::from_rgb(158, 158, 158))
-                            .rounding(egui::Rounding::same(3.0))
-                            .min_size(egui::vec2(80.0, 24.0));
-                            
-                            if ui.add(deselect_all_btn).clicked() {
-                                for result in &mut self.scan_results {
-                                    result.should_delete = false;
-                                }
-                            }
-                            
-                            ui.add_space(4.0);
-                            
-                            let select_all_btn = egui::Button::new(
-                                egui::RichText::new("✓ Select All").size(12.0).color(egui::Color32::WHITE)
-                            )
-                            .fill(egui::Color32::from_rgb(76, 175, 80))
-                            .rounding(egui::Rounding::same(3.0))
-                            .min_size(egui::vec2(80.0, 24.0));
-                            
-                            if ui.add(select_all_btn).clicked() {
-                                for result in &mut self.scan_results {
-                                    result.should_delete = true;
-                                }
-                            }
-                        });
-                    });
-                });
-                
-                ui.add_space(4.0);
-                
-                // Calculate available height for scroll area - use all available space
-                let available_height = ui.available_height();
-                
-                egui::ScrollArea::vertical()
-                    .max_height(available_height)
-                    .auto_shrink([false, false])
-                    .show(ui, |ui| {
-                        self.render_directory_tree(ui, 0);
-                    });
+            match self.selected_tab {
+                Tab::Settings => {
+                    egui::ScrollArea::vertical()
+                        .id_salt("settings")
+                        .auto_shrink([false, false])
+                        .show(ui, |ui| self.render_settings_tab(ui));
+                }
+                Tab::Results => self.render_results_tab(ui),
+                Tab::Log => {
+                    egui::ScrollArea::vertical()
+                        .id_salt("log")
+                        .auto_shrink([false, false])
+                        .show(ui, |ui| self.render_log_tab(ui));
+                }
             }
-            });
         });
     }
 }
 
-impl FileCleanerApp {
-    fn render_directory_tree(&mut self, ui: &mut egui::Ui, _depth: usize) {
-        // Build a tree structure mapping paths to their children
-        let mut tree: HashMap<String, Vec<String>> = HashMap::new();
-        let mut file_map: HashMap<String, Vec<usize>> = HashMap::new();
-        
-        for (idx, result) in self.scan_results.iter().enumerate() {
-            let path = std::path::Path::new(&result.file_path);
-            let dir = path.parent()
-                .and_then(|p| p.to_str())
-                .unwrap_or("")
-                .to_string();
-            
-            file_map.entry(dir.clone()).or_insert_with(Vec::new).push(idx);
-            
-            // Build parent-child relationships
-            if !dir.is_empty() {
-                let parts: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
-                
-                // Add all parent paths
-                for i in 0..parts.len() {
-                    let current_path = "/".to_string() + &parts[0..=i].join("/");
-                    
-                    if i > 0 {
-                        let parent_path = "/".to_string() + &parts[0..i].join("/");
-                        tree.entry(parent_path)
-                            .or_insert_with(Vec::new)
-                            .push(current_path.clone());
-                    }
-                }
-            }
-        }
-        
-        // Deduplicate children
-        for children in tree.values_mut() {
-            children.sort();
-            children.dedup();
-        }
-        
-        // Find root directories (those that appear in file_map but have a common ancestor)
-        let user = whoami::username();
-        let user_home = format!("/Users/{}", user);
-        
-        let mut roots: Vec<String> = file_map.keys()
-            .filter_map(|path| {
-                if path.starts_with(&user_home) {
-                    let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
-                    if parts.len() >= 3 {
-                        Some(format!("/{}/{}/{}", parts[0], parts[1], parts[2]))
-                    } else {
-                        None
-                    }
-                } else {
-                    Some(path.clone())
-                }
-            })
-            .collect();
-        
-        roots.sort();
-        roots.dedup();
-        
-        for root in roots {
-            self.render_tree_node(ui, &root, &tree, &file_map, 0);
-        }
+// Tenths of a second for quick scans, whole minutes and seconds past a minute
+fn human_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        format!("{}m {}s", secs / 60, secs % 60)
+    }
+}
+
+// How Command / Ctrl is spelled in shortcut hints
+const COMMAND_KEY: &str = if cfg!(target_os = "macos") { "Cmd" } else { "Ctrl" };
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+const TOP_RESULTS: usize = 20;
+
+// The TOP_RESULTS indices with the highest key, highest first. Only those get fully
+// sorted, which matters on scans with hundreds of thousands of results.
+fn top_results(indices: &[usize], key: impl Fn(usize) -> u64) -> Vec<usize> {
+    let mut top = indices.to_vec();
+    if top.len() > TOP_RESULTS {
+        top.select_nth_unstable_by_key(TOP_RESULTS - 1, |&idx| std::cmp::Reverse(key(idx)));
+        top.truncate(TOP_RESULTS);
+    }
+    top.sort_by_key(|&idx| std::cmp::Reverse(key(idx)));
+    top
+}
+
+// Rough age for reading at a glance; the exact day count goes in a tooltip
+fn relative_age(days: u64) -> String {
+    match days {
+        0 => "today".to_string(),
+        1 => "yesterday".to_string(),
+        2..=13 => format!("{} days ago", days),
+        14..=59 => format!("{} weeks ago", days / 7),
+        60..=364 => format!("{} months ago", days / 30),
+        _ => format!("{:.1} years ago", days as f64 / 365.0),
+    }
+}
+
+// A chrono format string with a mistake in it would panic when used, so it's
+// checked first; None means it can't be used
+fn format_date(time: std::time::SystemTime, format: &str) -> Option<String> {
+    let items = chrono::format::StrftimeItems::new(format).parse().ok()?;
+    Some(chrono::DateTime::<chrono::Local>::from(time).format_with_items(items.into_iter()).to_string())
+}
+
+// Either "3 weeks ago" or the date in the chosen format, falling back to ISO
+// dates while the format has a mistake in it
+fn format_age(result: &ScanResult, display: DateDisplay, format: &str) -> String {
+    match display {
+        DateDisplay::Relative => relative_age(result.days_since_access),
+        DateDisplay::Absolute => format_date(result.last_used, format)
+            .unwrap_or_else(|| format_date(result.last_used, "%Y-%m-%d").unwrap_or_default()),
+    }
+}
+
+// Hovering a file's name shows where it is, since two rows can share a name
+fn file_tooltip(result: &ScanResult) -> String {
+    format!("{}\n{} ({} bytes) · {} days old\n{}",
+        result.file_path.display(), human_size(result.size_bytes), result.size_bytes,
+        result.days_since_access, result.flag_reason)
+}
+
+// Every folder above the given ones mapped to its subfolders, sorted. Walking
+// Path::parent handles both separators and drive prefixes like `C:\`, which
+// splitting on '/' didn't.
+fn folder_tree(folders: &[PathBuf]) -> HashMap<String, Vec<String>> {
+    let mut tree: HashMap<String, Vec<String>> = HashMap::new();
+    for folder in folders {
+        let mut child = folder.as_path();
+        while let Some(parent) = child.parent() {
+            if parent.as_os_str().is_empty() {
+                break;
+            }
+            tree.entry(parent.to_string_lossy().to_string())
+                .or_default()
+                .push(child.to_string_lossy().to_string());
+            child = parent;
+        }
+    }
+    for children in tree.values_mut() {
+        children.sort();
+        children.dedup();
+    }
+    tree
+}
+
+// The tooltip on a result's name, with a preview for images
+fn file_hover(ui: &mut egui::Ui, result: &ScanResult, thumbnails: &mut thumbnails::Thumbnails) {
+    ui.label(file_tooltip(result));
+    if thumbnails::is_image(&result.file_path) {
+        thumbnails.show(ui, &result.file_path);
+    }
+}
+
+// `text` with the characters the search matched picked out. `positions` are char
+// positions in the full path, and `text` is the end of that path (the name, or all of it).
+fn highlighted_text(text: &str, path_chars: usize, positions: &[usize], size: f32) -> egui::text::LayoutJob {
+    let offset = path_chars.saturating_sub(text.chars().count());
+    let plain = egui::TextFormat {
+        font_id: egui::FontId::proportional(size),
+        color: egui::Color32::BLACK,
+        ..Default::default()
+    };
+    let matched = egui::TextFormat {
+        color: egui::Color32::from_rgb(33, 150, 243),
+        background: egui::Color32::from_rgb(227, 242, 253),
+        ..plain.clone()
+    };
+    
+    let mut job = egui::text::LayoutJob::default();
+    for (i, c) in text.chars().enumerate() {
+        let format = if positions.contains(&(offset + i)) { matched.clone() } else { plain.clone() };
+        job.append(c.encode_utf8(&mut [0; 4]), 0.0, format);
+    }
+    job
+}
+
+// Tag for empty files and dead links, the safest things in the list to remove
+fn hollow_badge(result: &ScanResult) -> Option<egui::RichText> {
+    let text = if result.broken_symlink {
+        "⛓ broken link"
+    } else if result.empty {
+        "∅ empty"
+    } else {
+        return None;
+    };
+    Some(egui::RichText::new(text)
+        .size(11.0)
+        .color(egui::Color32::from_rgb(255, 152, 0)))
+}
+
+// A checkbox that switches a size bound on, next to the value in MB. Unchecking
+// clears the bound; `default_mb` is what it starts at when switched on.
+fn size_bound_row(ui: &mut egui::Ui, label: &str, bound: &mut Option<u64>, default_mb: u64) {
+    ui.horizontal(|ui| {
+        let mut enabled = bound.is_some();
+        if ui.checkbox(&mut enabled, egui::RichText::new(label)
+                .size(12.0)
+                .color(egui::Color32::BLACK))
+            .changed()
+        {
+            *bound = enabled.then_some(default_mb);
+        }
+        
+        let mut mb = bound.unwrap_or(default_mb);
+        let response = ui.add_enabled(enabled,
+            egui::DragValue::new(&mut mb)
+                .range(0..=1_000_000)
+                .suffix(" MB"));
+        if response.changed() {
+            *bound = Some(mb);
+        }
+    });
+}
+
+// Multiline editor over a list of patterns. Lines are kept as typed (blank ones
+// included) so the cursor doesn't jump while editing.
+fn pattern_list_editor(ui: &mut egui::Ui, label: &str, patterns: &mut Vec<String>) {
+    ui.label(egui::RichText::new(label)
+        .size(12.0)
+        .color(egui::Color32::from_rgb(80, 80, 80)));
+    
+    let mut text = patterns.join("\n");
+    if ui.add(egui::TextEdit::multiline(&mut text)
+            .desired_rows(6)
+            .desired_width(f32::INFINITY)
+            .code_editor())
+        .changed()
+    {
+        *patterns = text.split('\n').map(str::to_string).collect();
+    }
+}
+
+// Finder selects the file itself; elsewhere the containing folder is opened
+#[cfg(target_os = "macos")]
+fn reveal_in_file_manager(path: &std::path::Path) -> Result<(), opener::OpenError> {
+    opener::reveal(path)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn reveal_in_file_manager(path: &std::path::Path) -> Result<(), opener::OpenError> {
+    opener::open(path.parent().unwrap_or(path))
+}
+
+// Quotes a CSV field when it contains a delimiter, quote, or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl FileCleanerApp {
+    // Defaults, overridden by whatever was saved last time
+    fn new(settings: Settings) -> Self {
+        let mut app = Self::default();
+        app.apply_settings(settings);
+        app.pending_restore = scan_cache::load().filter(|cache| !cache.results.is_empty());
+        app
+    }
+    
+    fn render_settings_tab(&mut self, ui: &mut egui::Ui) {
+        ui.add_space(8.0);
+        
+        // Aggressiveness presets bundle the individual settings below
+        let preset_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        preset_frame.show(ui, |ui| {
+            ui.label(egui::RichText::new("🎚️ Preset")
+                .size(14.0)
+                .strong()
+                .color(egui::Color32::BLACK));
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                for preset in Preset::ALL {
+                    if ui.selectable_label(self.preset == preset, preset.label())
+                        .on_hover_text(preset.description())
+                        .clicked()
+                    {
+                        self.apply_preset(preset);
+                    }
+                }
+                
+                ui.add_space(8.0);
+                let suggest_btn = egui::Button::new(
+                    egui::RichText::new("🧹 Suggest cleanup").size(12.0).color(egui::Color32::WHITE)
+                )
+                .fill(egui::Color32::from_rgb(156, 39, 176))
+                .rounding(egui::Rounding::same(3.0));
+                
+                if ui.add_enabled(!self.is_scanning, suggest_btn)
+                    .on_hover_text("Switch to the Junk preset and scan for everything on the junk list")
+                    .clicked()
+                {
+                    self.apply_preset(Preset::Junk);
+                    self.scan_files();
+                }
+            });
+            if !self.matches_preset() {
+                ui.label(egui::RichText::new("Adjusted from preset")
+                    .size(11.0)
+                    .italics()
+                    .color(egui::Color32::from_rgb(120, 120, 120)));
+            }
+            
+            // The junk list is the whole of what that preset looks for, so keep it in view
+            if self.preset == Preset::Junk {
+                ui.add_space(6.0);
+                pattern_list_editor(ui,
+                    "Junk file names, older than the time limit, e.g. *.crdownload or Thumbs.db",
+                    &mut self.junk_patterns);
+                
+                let (_, invalid) = scanner::junk_globs(&self.junk_patterns);
+                for pattern in invalid {
+                    ui.label(egui::RichText::new(format!("⚠️ {} is not a valid pattern and is ignored", pattern))
+                        .size(11.0)
+                        .color(egui::Color32::from_rgb(244, 67, 54)));
+                }
+                if ui.button(egui::RichText::new("Reset to defaults").size(12.0)).clicked() {
+                    self.junk_patterns = scanner::default_junk_patterns();
+                }
+            }
+        });
+        ui.add_space(8.0);
+        
+        // Time limit section with better styling
+        let settings_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        settings_frame.show(ui, |ui| {
+            ui.label(egui::RichText::new("⏰ Time Threshold")
+                .size(14.0)
+                .strong()
+                .color(egui::Color32::BLACK));
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Judge age by:")
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(80, 80, 80)));
+                let single = self.time_combiner == TimeCombiner::Single;
+                for basis in TimeBasis::ALL {
+                    ui.add_enabled_ui(single, |ui| {
+                        ui.radio_value(&mut self.time_basis, basis, basis.verb());
+                    });
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Old by:")
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(80, 80, 80)));
+                for combiner in TimeCombiner::ALL {
+                    ui.radio_value(&mut self.time_combiner, combiner, combiner.label());
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("Delete files not {} in:", self.time_combiner.verb(self.time_basis)))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(80, 80, 80)));
+                ui.add(egui::Slider::new(&mut self.time_limit, 1..=self.time_unit.max_amount()));
+                egui::ComboBox::from_id_salt("time_unit")
+                    .selected_text(self.time_unit.label())
+                    .width(80.0)
+                    .show_ui(ui, |ui| {
+                        for unit in TimeUnit::ALL {
+                            ui.selectable_value(&mut self.time_unit, unit, unit.label());
+                        }
+                    });
+                // Switching to a bigger unit would otherwise leave the slider past its end
+                self.time_limit = self.time_limit.min(self.time_unit.max_amount());
+            });
+            ui.horizontal(|ui| {
+                let mut protected = self.protect_modified_days.is_some();
+                if ui.checkbox(&mut protected, egui::RichText::new("✏️ Never flag files modified within:")
+                        .size(12.0)
+                        .color(egui::Color32::BLACK))
+                    .on_hover_text("Checked on top of the age above, so files you edit often are safe \
+                        even when their access time is old")
+                    .changed()
+                {
+                    self.protect_modified_days = protected.then_some(7);
+                }
+                
+                let mut days = self.protect_modified_days.unwrap_or(7);
+                if ui.add_enabled(protected, egui::DragValue::new(&mut days).range(1..=365).suffix(" days")).changed() {
+                    self.protect_modified_days = Some(days);
+                }
+            });
+            ui.add_space(4.0);
+            ui.checkbox(&mut self.usage_signal_enabled,
+                egui::RichText::new("📈 Consider how often files are used (macOS Spotlight)")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK))
+                .on_hover_text("Rarely used files can be flagged even if opened recently, and \
+                    frequently used files are kept even if old. Falls back to timestamps \
+                    where no usage data exists. Slows down scanning.");
+            if self.usage_signal_enabled {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Keep files used at least:")
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(80, 80, 80)));
+                    ui.add(egui::Slider::new(&mut self.frequent_use_count, 2..=100)
+                        .suffix(" times"));
+                });
+            }
+        });
+        ui.add_space(8.0);
+        
+        // Directory selection
+        let dir_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        dir_frame.show(ui, |ui| {
+            ui.label(egui::RichText::new("📁 Directories to Search")
+                .size(14.0)
+                .strong()
+                .color(egui::Color32::BLACK));
+            ui.add_space(6.0);
+            ui.checkbox(&mut self.downloads_enabled, 
+                egui::RichText::new("📥 Downloads").size(12.0).color(egui::Color32::BLACK));
+            ui.checkbox(&mut self.documents_enabled, 
+                egui::RichText::new("📝 Documents").size(12.0).color(egui::Color32::BLACK));
+            ui.checkbox(&mut self.desktop_enabled, 
+                egui::RichText::new("🖥️ Desktop").size(12.0).color(egui::Color32::BLACK));
+            ui.checkbox(&mut self.pictures_enabled, 
+                egui::RichText::new("🖼️ Pictures").size(12.0).color(egui::Color32::BLACK));
+            ui.checkbox(&mut self.videos_enabled, 
+                egui::RichText::new("🎬 Videos").size(12.0).color(egui::Color32::BLACK));
+            ui.checkbox(&mut self.music_enabled, 
+                egui::RichText::new("🎵 Music").size(12.0).color(egui::Color32::BLACK));
+            ui.add_enabled(trash_bin::CAN_SCAN_TRASH, egui::Checkbox::new(&mut self.trash_scan_enabled, 
+                egui::RichText::new("🗑️ Trash").size(12.0).color(egui::Color32::BLACK)))
+                .on_hover_text("Find things that have been sitting in the Trash. They are removed permanently.")
+                .on_disabled_hover_text("The Recycle Bin can't be scanned on Windows");
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("🏠 Home directory override:")
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(80, 80, 80)));
+                ui.add(egui::TextEdit::singleline(&mut self.home_directory)
+                    .hint_text(settings::home_dir("").to_string_lossy()))
+                    .on_hover_text("The built-in folders above are looked for here instead. Leave empty to use the system's.");
+            });
+        });
+        ui.add_space(8.0);
+        
+        // Custom directories below
+        let custom_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        custom_frame.show(ui, |ui| {
+            ui.label(egui::RichText::new("➕ Custom Directories")
+                .size(14.0)
+                .strong()
+                .color(egui::Color32::BLACK));
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Path:").size(12.0).color(egui::Color32::from_rgb(80, 80, 80)));
+                let path_response = ui.text_edit_singleline(&mut self.new_directory);
+                
+                let add_btn = egui::Button::new(
+                    egui::RichText::new("Add").size(12.0).color(egui::Color32::WHITE)
+                )
+                .fill(egui::Color32::from_rgb(76, 175, 80))
+                .rounding(egui::Rounding::same(3.0))
+                .min_size(egui::vec2(50.0, 24.0));
+                
+                let add_clicked = ui.add(add_btn).clicked();
+                let enter_pressed = path_response.lost_focus()
+                    && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                
+                if add_clicked || enter_pressed {
+                    self.add_custom_directory();
+                    // Keep the cursor in the field so several paths can be typed in a row
+                    if enter_pressed {
+                        path_response.request_focus();
+                    }
+                }
+            });
+            
+            if let Some(error) = &self.directory_error {
+                ui.label(egui::RichText::new(format!("⚠️ {}", error))
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(244, 67, 54)));
+            }
+            
+            // Display custom directories
+            if !self.custom_directories.is_empty() {
+                ui.add_space(6.0);
+            }
+            let mut to_remove = None;
+            for (idx, dir) in self.custom_directories.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(format!("📂 {}", dir))
+                        .size(11.0)
+                        .color(egui::Color32::from_rgb(80, 80, 80)));
+                    
+                    let remove_btn = egui::Button::new(
+                        egui::RichText::new("✕").size(11.0).color(egui::Color32::WHITE)
+                    )
+                    .fill(egui::Color32::from_rgb(244, 67, 54))
+                    .rounding(egui::Rounding::same(2.0))
+                    .min_size(egui::vec2(24.0, 18.0));
+                    
+                    if ui.add(remove_btn).clicked() {
+                        to_remove = Some(idx);
+                    }
+                });
+            }
+            if let Some(idx) = to_remove {
+                self.custom_directories.remove(idx);
+            }
+        });
+        ui.add_space(8.0);
+        
+        // Glob patterns for paths the scan never looks at
+        let exclude_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        let mut open_ignore_file = false;
+        exclude_frame.show(ui, |ui| {
+            ui.label(egui::RichText::new("🚫 Exclude Patterns")
+                .size(14.0)
+                .strong()
+                .color(egui::Color32::BLACK));
+            ui.add_space(6.0);
+            pattern_list_editor(ui,
+                "Matched against the full path, e.g. */node_modules or */Documents/ImportantArchive",
+                &mut self.exclude_patterns);
+            
+            let (_, invalid) = scanner::exclude_globs(&self.exclude_patterns);
+            for pattern in invalid {
+                ui.label(egui::RichText::new(format!("⚠️ {} is not a valid pattern and is ignored", pattern))
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(244, 67, 54)));
+            }
+            
+            ui.add_space(6.0);
+            ui.horizontal_wrapped(|ui| {
+                ui.label(egui::RichText::new(format!(
+                    "Rules are also read from {} files in scanned folders, written like .gitignore, and from a global one.",
+                    cleanignore::FILE_NAME))
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(100, 100, 100)));
+                if let Some(path) = cleanignore::global_path() {
+                    open_ignore_file = ui.small_button("📝 Edit global file")
+                        .on_hover_text(path.display().to_string())
+                        .clicked();
+                }
+            });
+            ui.checkbox(&mut self.respect_gitignore, "🌿 Skip what .gitignore leaves out in git projects")
+                .on_hover_text("Reads each project's .gitignore files the way git does. Inside a project \
+                    they decide what's build output, in place of the smart filter's build folder names.");
+        });
+        if open_ignore_file && let Some(path) = cleanignore::global_path() {
+            // Created empty on first use so there's something to open
+            let opened = path.parent().map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::OpenOptions::new().create(true).append(true).open(&path).map(|_| ()))
+                .map_err(|e| e.to_string())
+                .and_then(|()| opener::open(&path).map_err(|e| e.to_string()));
+            if let Err(e) = opened {
+                self.status_message = format!("❌ Could not open {}: {}", path.display(), e);
+            }
+        }
+        ui.add_space(8.0);
+        
+        // Folders that are off limits to both the scan and deletion
+        let protected_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        protected_frame.show(ui, |ui| {
+            ui.label(egui::RichText::new("🛡️ Protected Paths")
+                .size(14.0)
+                .strong()
+                .color(egui::Color32::BLACK));
+            ui.add_space(6.0);
+            let example = std::path::Path::new(
+                &settings::user_dir(&self.home_directory, directories::UserDirs::document_dir, "Documents")).join("Legal");
+            pattern_list_editor(ui,
+                &format!("Nothing inside these folders is ever scanned or deleted, e.g. {}", example.display()),
+                &mut self.protected_paths);
+        });
+        ui.add_space(8.0);
+        
+        // Files that keep getting flagged but should stay
+        let keep_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        keep_frame.show(ui, |ui| {
+            ui.label(egui::RichText::new("🔒 Never Delete")
+                .size(14.0)
+                .strong()
+                .color(egui::Color32::BLACK));
+            ui.add_space(6.0);
+            pattern_list_editor(ui,
+                "Files matching these are still listed but can't be selected, e.g. */license*.txt or *.pdf",
+                &mut self.never_delete_patterns);
+            
+            let (_, invalid) = scanner::exclude_globs(&self.never_delete_patterns);
+            for pattern in invalid {
+                ui.label(egui::RichText::new(format!("⚠️ {} is not a valid pattern and is ignored", pattern))
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(244, 67, 54)));
+            }
+            
+            if !self.never_delete.is_empty() {
+                let mut kept: Vec<String> = self.never_delete.iter().cloned().collect();
+                kept.sort();
+                
+                ui.add_space(4.0);
+                egui::CollapsingHeader::new(
+                    egui::RichText::new(format!("{} files marked Keep", kept.len())).size(12.0)
+                )
+                .id_salt("never_delete")
+                .show(ui, |ui| {
+                    for path in kept {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("✖").on_hover_text("Stop keeping this file").clicked() {
+                                self.never_delete.remove(&path);
+                            }
+                            ui.label(egui::RichText::new(&path)
+                                .size(11.0)
+                                .color(egui::Color32::from_rgb(80, 80, 80)));
+                        });
+                    }
+                });
+            }
+        });
+        ui.add_space(8.0);
+        
+        // Supporting files swept up along with a program
+        let associated_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        associated_frame.show(ui, |ui| {
+            ui.label(egui::RichText::new("🧩 Associated Files")
+                .size(14.0)
+                .strong()
+                .color(egui::Color32::BLACK));
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("When deleting a file ending in")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK));
+                ui.add(egui::TextEdit::singleline(&mut self.associated_trigger_extension)
+                    .desired_width(60.0))
+                    .on_hover_text("Leave empty to never remove associated files");
+            });
+            pattern_list_editor(ui,
+                "also remove files with the same name ending in",
+                &mut self.associated_extensions);
+        });
+        ui.add_space(8.0);
+        
+        // Smart filter option
+        let smart_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        smart_frame.show(ui, |ui| {
+            ui.checkbox(&mut self.smart_filter_enabled, 
+                egui::RichText::new("🧠 Smart Filter (exclude binary/system files)")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK));
+            if self.smart_filter_enabled {
+                self.render_filter_rules(ui);
+                ui.checkbox(&mut self.list_filtered, 
+                    egui::RichText::new("🔎 List the files it leaves out in the Log tab")
+                        .size(12.0)
+                        .color(egui::Color32::BLACK))
+                    .on_hover_text("To check the rules. Slows down scans that filter out a lot.");
+            }
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.incomplete_downloads_enabled, 
+                    egui::RichText::new("⏬ Flag incomplete downloads untouched for")
+                        .size(12.0)
+                        .color(egui::Color32::BLACK));
+                ui.add_enabled(self.incomplete_downloads_enabled,
+                    egui::DragValue::new(&mut self.incomplete_download_days)
+                        .range(0..=30)
+                        .suffix(" days"));
+            });
+            size_bound_row(ui, "📏 Only files of at least", &mut self.min_size_mb, 10);
+            size_bound_row(ui, "📏 Only files of at most", &mut self.max_size_mb, 1_024);
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("🎯 Only files ending in")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK));
+                // Split as typed, not trimmed, so the cursor stays put after a comma
+                let mut text = self.include_extensions.join(",");
+                if ui.add(egui::TextEdit::singleline(&mut text)
+                        .hint_text("zip, iso, dmg")
+                        .desired_width(160.0))
+                    .on_hover_text("Comma-separated. Leave empty to look at every file the smart filter lets through.")
+                    .changed()
+                {
+                    self.include_extensions = if text.is_empty() {
+                        Vec::new()
+                    } else {
+                        text.split(',').map(str::to_string).collect()
+                    };
+                }
+            });
+            if !scanner::extension_list(&self.include_extensions).is_empty() {
+                ui.label(egui::RichText::new("The smart filter is ignored while this is set")
+                    .size(11.0)
+                    .italics()
+                    .color(egui::Color32::from_rgb(120, 120, 120)));
+            }
+            ui.checkbox(&mut self.duplicates_enabled, 
+                egui::RichText::new("👯 Find duplicate files, whatever their age")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK))
+                .on_hover_text("Files with the same size are compared by content after the scan. \
+                    One copy of each is kept, the rest are selected.");
+            ui.checkbox(&mut self.preselect_results, 
+                egui::RichText::new("☑️ Pre-select found files for deletion")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK));
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Expand all folders when there are at most:")
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(80, 80, 80)));
+                ui.add(egui::DragValue::new(&mut self.auto_expand_below)
+                    .range(0..=1_000)
+                    .suffix(" results"));
+            });
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Hold off on directories with more than:")
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(80, 80, 80)));
+                ui.add(egui::DragValue::new(&mut self.large_dir_threshold)
+                    .range(100..=1_000_000)
+                    .speed(100.0)
+                    .suffix(" entries"));
+            });
+            ui.horizontal(|ui| {
+                let mut limited = self.max_depth.is_some();
+                if ui.checkbox(&mut limited, egui::RichText::new("📐 Only go this many folders deep:")
+                        .size(12.0)
+                        .color(egui::Color32::BLACK))
+                    .on_hover_text("0 scans just the files directly inside each directory")
+                    .changed()
+                {
+                    self.max_depth = limited.then_some(3);
+                }
+                
+                let mut depth = self.max_depth.unwrap_or(3);
+                if ui.add_enabled(limited, egui::Slider::new(&mut depth, 0..=20)).changed() {
+                    self.max_depth = Some(depth);
+                }
+            });
+            ui.checkbox(&mut self.follow_symlinks, 
+                egui::RichText::new("🔗 Follow symlinked folders")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK))
+                .on_hover_text("Folders reached through more than one link are still only scanned once");
+            ui.checkbox(&mut self.include_hidden, 
+                egui::RichText::new("👻 Include hidden files")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK))
+                .on_hover_text("Dotfiles like .DS_Store, and files marked hidden on Windows");
+        });
+        ui.add_space(8.0);
+        
+        // Sandbox mode runs everything against a temporary copy
+        let sandbox_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        sandbox_frame.show(ui, |ui| {
+            ui.checkbox(&mut self.sandbox_mode, 
+                egui::RichText::new("🧪 Sandbox mode (scan and delete a temporary copy)")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK));
+            
+            if let Some(root) = &self.sandbox_root {
+                ui.add_space(4.0);
+                let mut discard = false;
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(format!("📦 {}", root.display()))
+                        .size(11.0)
+                        .color(egui::Color32::from_rgb(80, 80, 80)));
+                    
+                    let discard_btn = egui::Button::new(
+                        egui::RichText::new("Discard").size(11.0).color(egui::Color32::WHITE)
+                    )
+                    .fill(egui::Color32::from_rgb(158, 158, 158))
+                    .rounding(egui::Rounding::same(2.0))
+                    .min_size(egui::vec2(60.0, 18.0));
+                    
+                    discard = ui.add(discard_btn).clicked();
+                });
+                
+                if discard {
+                    self.discard_sandbox();
+                    self.scan_results.clear();
+                    self.result_tree = None;
+                    self.status_message = "Sandbox discarded.".to_string();
+                }
+            }
+        });
+        ui.add_space(8.0);
+        
+        // Archive destination for files to keep but move off this disk
+        let archive_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        archive_frame.show(ui, |ui| {
+            ui.label(egui::RichText::new("📦 Archive Destination")
+                .size(14.0)
+                .strong()
+                .color(egui::Color32::BLACK));
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Path:").size(12.0).color(egui::Color32::from_rgb(80, 80, 80)));
+                ui.text_edit_singleline(&mut self.archive_destination)
+                    .on_hover_text("Selected files are moved here, keeping their folder structure");
+            });
+            ui.checkbox(&mut self.zip_remove_originals, 
+                egui::RichText::new("🗜️ Remove the originals after zipping them")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK))
+                .on_hover_text("Once the whole zip has been written, after the usual delete confirmation");
+        });
+        ui.add_space(8.0);
+        
+        // Scans that run on their own every few days
+        let schedule_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        schedule_frame.show(ui, |ui| {
+            ui.label(egui::RichText::new("🕒 Scheduled Scans")
+                .size(14.0)
+                .strong()
+                .color(egui::Color32::BLACK));
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                let mut enabled = self.scheduled_scan_days.is_some();
+                let hint = if tray::SUPPORTED {
+                    "Closing the window keeps PinnacleSort in the tray so scans keep running. Use Quit in the tray menu to exit."
+                } else {
+                    "Scans only run while PinnacleSort is open"
+                };
+                if ui.checkbox(&mut enabled, egui::RichText::new("Scan in the background every")
+                        .size(12.0)
+                        .color(egui::Color32::BLACK))
+                    .on_hover_text(hint)
+                    .changed()
+                {
+                    self.scheduled_scan_days = enabled.then_some(7);
+                }
+                
+                let mut days = self.scheduled_scan_days.unwrap_or(7);
+                if ui.add_enabled(enabled, egui::DragValue::new(&mut days).range(1..=90).suffix(" days")).changed() {
+                    self.scheduled_scan_days = Some(days);
+                }
+            });
+            ui.label(egui::RichText::new("Uses the saved settings and notifies you when it finds something. Nothing is removed without you.")
+                .size(11.0)
+                .color(egui::Color32::from_rgb(120, 120, 120)));
+        });
+        ui.add_space(8.0);
+        
+        // How the results show when each file was last used
+        let dates_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        dates_frame.show(ui, |ui| {
+            ui.label(egui::RichText::new("📅 Dates")
+                .size(14.0)
+                .strong()
+                .color(egui::Color32::BLACK));
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Show last use as:").size(12.0).color(egui::Color32::from_rgb(80, 80, 80)));
+                for display in DateDisplay::ALL {
+                    ui.radio_value(&mut self.date_display, display, display.label());
+                }
+            });
+            ui.add_enabled_ui(self.date_display == DateDisplay::Absolute, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Format:").size(12.0).color(egui::Color32::from_rgb(80, 80, 80)));
+                    ui.add(egui::TextEdit::singleline(&mut self.date_format).desired_width(140.0))
+                        .on_hover_text("chrono format, e.g. %Y-%m-%d, %d/%m/%Y or %x for your locale's own");
+                    match format_date(std::time::SystemTime::now(), &self.date_format) {
+                        Some(example) => ui.label(egui::RichText::new(format!("Today: {}", example))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(100, 100, 100))),
+                        None => ui.label(egui::RichText::new("⚠ Not a valid format, showing %Y-%m-%d")
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(244, 67, 54))),
+                    };
+                });
+            });
+        });
+        ui.add_space(8.0);
+        
+        // Trash / Recycle Bin reporting
+        let trash_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(250, 250, 250))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 220, 220)))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        trash_frame.show(ui, |ui| {
+            ui.checkbox(&mut self.permanent_delete, 
+                egui::RichText::new("⚠️ Delete permanently instead of moving to Trash")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK))
+                .on_hover_text("Deleted files can't be recovered");
+            
+            ui.add_enabled(self.permanent_delete, egui::Checkbox::new(&mut self.secure_delete,
+                egui::RichText::new("🔏 Secure delete (overwrite with zeros first)")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK)))
+                .on_disabled_hover_text("Only files deleted permanently can be overwritten");
+            if self.permanent_delete && self.secure_delete {
+                ui.label(egui::RichText::new("Slow for large files, and not reliable on SSDs or copy-on-write \
+                    filesystems (APFS, Btrfs, ZFS): they write the zeros elsewhere and the old \
+                    data lingers until the space is reused.")
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(255, 152, 0)));
+            }
+            
+            // Only Windows refuses to delete a file because of its read-only attribute
+            if cfg!(windows) {
+                ui.checkbox(&mut self.force_read_only, 
+                    egui::RichText::new("🔓 Force delete read-only files")
+                        .size(12.0)
+                        .color(egui::Color32::BLACK))
+                    .on_hover_text("Clear the read-only attribute and try again when a file can't be removed");
+            }
+            
+            ui.checkbox(&mut self.dry_run, 
+                egui::RichText::new("👀 Dry run (preview deletions without removing anything)")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK));
+            
+            ui.checkbox(&mut self.remove_emptied_folders, 
+                egui::RichText::new("📁 Remove emptied folders")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK))
+                .on_hover_text("After deleting, remove folders left with nothing in them. The scanned folders themselves are kept.");
+            
+            ui.horizontal(|ui| {
+                let history_btn = egui::Button::new(
+                    egui::RichText::new("📜 View History").size(12.0).color(egui::Color32::WHITE)
+                )
+                .fill(egui::Color32::from_rgb(96, 125, 139))
+                .rounding(egui::Rounding::same(3.0));
+                
+                if ui.add(history_btn).clicked() {
+                    match history::load() {
+                        Ok(lines) => self.history_lines = Some(lines),
+                        Err(e) => self.status_message = format!("❌ Could not read the deletion history: {}", e),
+                    }
+                }
+                
+                ui.label(egui::RichText::new("Start a new log after:")
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(80, 80, 80)));
+                ui.add(egui::DragValue::new(&mut self.history_max_kb)
+                    .range(16..=102_400)
+                    .speed(16.0)
+                    .suffix(" KB"))
+                    .on_hover_text("The previous log is kept as history.log.1");
+            });
+            
+            let trash_toggle = ui.checkbox(&mut self.trash_report_enabled, 
+                egui::RichText::new("🗑️ Include Trash size in reporting")
+                    .size(12.0)
+                    .color(egui::Color32::BLACK));
+            if trash_toggle.changed() && self.trash_report_enabled {
+                self.refresh_trash();
+            }
+            
+            if self.trash_report_enabled {
+                ui.add_space(6.0);
+                self.render_trash_report(ui);
+            }
+        });
+        ui.add_space(8.0);
+    }
+    
+    fn render_results_tab(&mut self, ui: &mut egui::Ui) {
+        self.render_scheduled_results(ui);
+        if !self.drive_space.is_empty() {
+            self.render_drive_space(ui);
+            ui.add_space(4.0);
+        }
+        
+        // Results section
+        if !self.scan_results.is_empty() {
+            let selected_count = self.scan_results.iter().filter(|r| r.should_delete).count();
+            
+            // Compact heading with background
+            let header_frame = egui::Frame::none()
+                .fill(egui::Color32::from_rgb(245, 245, 245))
+                .inner_margin(egui::Margin::symmetric(8.0, 6.0))
+                .rounding(egui::Rounding::same(0.0));
+            
+            header_frame.show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(
+                        format!("📊 {} files  •  {} selected ({})", 
+                            self.scan_results.len(), selected_count, human_size(self.selected_bytes()))
+                    ).size(13.0).strong());
+                    
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        // The list only changes once the deletion is over
+                        if self.deletion.is_some() {
+                            self.render_delete_progress(ui);
+                            return;
+                        }
+                        if let Some(zipping) = &self.zipping {
+                            ui.spinner();
+                            ui.label(egui::RichText::new(format!(
+                                "🗜️ Zipping {} files into {}…", zipping.files.len(), zipping.zip_path.display()))
+                                .size(12.0)
+                                .color(egui::Color32::from_rgb(100, 100, 100)));
+                            return;
+                        }
+                        if let Some(archiving) = &self.archiving {
+                            ui.add(egui::ProgressBar::new(archiving.fraction())
+                                .desired_width(220.0)
+                                .fill(egui::Color32::from_rgb(121, 85, 72))
+                                .text(format!("📦 {} / {} files", archiving.outcomes.len(), archiving.total)));
+                            return;
+                        }
+                        
+                        let marked_count = self.marked_folders.len();
+                        if selected_count > 0 || marked_count > 0 {
+                            let delete_label = if marked_count > 0 {
+                                format!("🗑️ Delete {} + {} folders", selected_count, marked_count)
+                            } else {
+                                format!("🗑️ Delete {}", selected_count)
+                            };
+                            let delete_btn = egui::Button::new(
+                                egui::RichText::new(delete_label)
+                                    .size(12.0)
+                                    .color(egui::Color32::WHITE)
+                            )
+                            .fill(egui::Color32::from_rgb(244, 67, 54))
+                            .rounding(egui::Rounding::same(3.0))
+                            .min_size(egui::vec2(90.0, 24.0));
+                            
+                            if ui.add(delete_btn).on_hover_text("Delete key").clicked() {
+                                self.open_delete_confirm(DeleteScope::Selected);
+                            }
+                            ui.add_space(4.0);
+                            
+                            let archive_btn = egui::Button::new(
+                                egui::RichText::new(format!("📦 Archive {}", selected_count))
+                                    .size(12.0)
+                                    .color(egui::Color32::WHITE)
+                            )
+                            .fill(egui::Color32::from_rgb(121, 85, 72))
+                            .rounding(egui::Rounding::same(3.0))
+                            .min_size(egui::vec2(90.0, 24.0));
+                            
+                            let destination = self.archive_destination.trim().to_string();
+                            let archive_hint = if self.dry_run {
+                                "Archiving moves files for real, so it's off during a dry run"
+                            } else {
+                                "Set an archive destination in the settings first"
+                            };
+                            if ui.add_enabled(selected_count > 0 && !destination.is_empty() && !self.dry_run, archive_btn)
+                                .on_disabled_hover_text(archive_hint)
+                                .clicked()
+                            {
+                                self.archive_files(std::path::Path::new(&destination));
+                            }
+                            ui.add_space(4.0);
+                            
+                            let zip_btn = egui::Button::new(
+                                egui::RichText::new(format!("🗜️ Zip {}", selected_count))
+                                    .size(12.0)
+                                    .color(egui::Color32::WHITE)
+                            )
+                            .fill(egui::Color32::from_rgb(121, 85, 72))
+                            .rounding(egui::Rounding::same(3.0))
+                            .min_size(egui::vec2(70.0, 24.0));
+                            
+                            if ui.add_enabled(selected_count > 0, zip_btn).on_hover_text("Pack the selected files into one dated zip").clicked() {
+                                self.zip_selected_dialog();
+                            }
+                            ui.add_space(4.0);
+                        }
+                        
+                        let failed_count = self.scan_results.iter().filter(|r| r.delete_error.is_some() && !r.kept).count();
+                        if failed_count > 0 {
+                            let retry_btn = egui::Button::new(
+                                egui::RichText::new(format!("🔁 Retry failed ({})", failed_count))
+                                    .size(12.0)
+                                    .color(egui::Color32::WHITE)
+                            )
+                            .fill(egui::Color32::from_rgb(255, 152, 0))
+                            .rounding(egui::Rounding::same(3.0))
+                            .min_size(egui::vec2(90.0, 24.0));
+                            
+                            if ui.add(retry_btn)
+                                .on_hover_text("Try removing the files that couldn't be removed last time, whether or not they're selected")
+                                .clicked()
+                            {
+                                self.open_delete_confirm(DeleteScope::Failed);
+                            }
+                            ui.add_space(4.0);
+                        }
+                        
+                        let deselect_all_btn = egui::Button::new(
+                            egui::RichText::new("✗ Deselect").size(12.0).color(egui::Color32::WHITE)
+                        )
+                        .fill(egui::Color32::from_rgb(158, 158, 158))
+                        .rounding(egui::Rounding::same(3.0))
+                        .min_size(egui::vec2(80.0, 24.0));
+                        
+                        if ui.add(deselect_all_btn).on_hover_text(format!("{}+D", COMMAND_KEY)).clicked() {
+                            for result in &mut self.scan_results {
+                                result.should_delete = false;
+                            }
+                        }
+                        
+                        ui.add_space(4.0);
+                        
+                        let invert_btn = egui::Button::new(
+                            egui::RichText::new("🔄 Invert").size(12.0).color(egui::Color32::WHITE)
+                        )
+                        .fill(egui::Color32::from_rgb(96, 125, 139))
+                        .rounding(egui::Rounding::same(3.0))
+                        .min_size(egui::vec2(80.0, 24.0));
+                        
+                        if ui.add(invert_btn).clicked() {
+                            for result in &mut self.scan_results {
+                                result.select(!result.should_delete);
+                            }
+                        }
+                        
+                        ui.add_space(4.0);
+                        
+                        let select_all_btn = egui::Button::new(
+                            egui::RichText::new("✓ Select All").size(12.0).color(egui::Color32::WHITE)
+                        )
+                        .fill(egui::Color32::from_rgb(76, 175, 80))
+                        .rounding(egui::Rounding::same(3.0))
+                        .min_size(egui::vec2(80.0, 24.0));
+                        
+                        if ui.add(select_all_btn)
+                            .on_hover_text(format!("{}+A. Shift-click a checkbox to select the rows since the last one clicked.", COMMAND_KEY))
+                            .clicked()
+                        {
+                            for result in &mut self.scan_results {
+                                result.select(true);
+                            }
+                        }
+                        
+                        ui.add_space(4.0);
+                        
+                        let export_btn = egui::Button::new(
+                            egui::RichText::new("📄 Export CSV").size(12.0).color(egui::Color32::WHITE)
+                        )
+                        .fill(egui::Color32::from_rgb(33, 150, 243))
+                        .rounding(egui::Rounding::same(3.0))
+                        .min_size(egui::vec2(90.0, 24.0));
+                        
+                        if ui.add(export_btn).clicked() {
+                            self.export_csv_dialog();
+                        }
+                        
+                        let report_btn = egui::Button::new(
+                            egui::RichText::new("🧾 Export JSON").size(12.0).color(egui::Color32::WHITE)
+                        )
+                        .fill(egui::Color32::from_rgb(33, 150, 243))
+                        .rounding(egui::Rounding::same(3.0))
+                        .min_size(egui::vec2(90.0, 24.0));
+                        
+                        if ui.add(report_btn).on_hover_text("Full scan report with the settings used, for scripts and dashboards").clicked() {
+                            self.export_report_dialog();
+                        }
+                    });
+                });
+            });
+            
+            ui.add_space(4.0);
+            
+            ui.horizontal(|ui| {
+                for mode in ViewMode::ALL {
+                    ui.selectable_value(&mut self.view_mode, mode, egui::RichText::new(mode.label()).size(12.0));
+                }
+                ui.add_space(12.0);
+                
+                ui.label(egui::RichText::new("Sort by:").size(12.0).color(egui::Color32::from_rgb(80, 80, 80)));
+                egui::ComboBox::from_id_salt("sort_key")
+                    .selected_text(self.sort_key.label())
+                    .show_ui(ui, |ui| {
+                        for key in SortKey::ALL {
+                            ui.selectable_value(&mut self.sort_key, key, key.label());
+                        }
+                    });
+                
+                let direction = if self.sort_descending { "⬇ Descending" } else { "⬆ Ascending" };
+                if ui.button(egui::RichText::new(direction).size(12.0)).clicked() {
+                    self.sort_descending = !self.sort_descending;
+                }
+                
+                ui.add_space(12.0);
+                ui.add(egui::TextEdit::singleline(&mut self.search_query)
+                    .hint_text("🔎 Fuzzy search paths")
+                    .desired_width(180.0));
+                if !self.search_query.is_empty() && ui.small_button("✖").clicked() {
+                    self.search_query.clear();
+                }
+                
+                ui.add_space(12.0);
+                for filter in SelectionFilter::ALL {
+                    ui.selectable_value(&mut self.selection_filter, filter, egui::RichText::new(filter.label()).size(12.0));
+                }
+            });
+            
+            // Adds to the selection across every result, whatever the filters show
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Quick select:").size(12.0).color(egui::Color32::from_rgb(80, 80, 80)));
+                ui.add(egui::DragValue::new(&mut self.quick_select_days)
+                    .range(0..=36_500)
+                    .suffix(" days"));
+                if ui.button(egui::RichText::new("✓ Select older than").size(12.0)).clicked() {
+                    let days = self.quick_select_days;
+                    self.select_where(|result| result.days_since_access >= days);
+                }
+                
+                ui.add_space(12.0);
+                ui.add(egui::DragValue::new(&mut self.quick_select_mb)
+                    .range(0..=1_000_000)
+                    .suffix(" MB"));
+                if ui.button(egui::RichText::new("✓ Select larger than").size(12.0)).clicked() {
+                    let bytes = self.quick_select_mb * 1024 * 1024;
+                    self.select_where(|result| result.size_bytes >= bytes);
+                }
+                
+                let hollow = self.scan_results.iter().filter(|r| r.empty || r.broken_symlink).count();
+                if hollow > 0 {
+                    ui.add_space(12.0);
+                    if ui.button(egui::RichText::new(format!("✓ Select all empty/broken ({})", hollow)).size(12.0))
+                        .on_hover_text("Zero-byte files and links whose target is gone")
+                        .clicked()
+                    {
+                        self.select_where(|result| result.empty || result.broken_symlink);
+                    }
+                }
+            });
+            
+            ui.add_space(4.0);
+            
+            // Calculate available height for scroll area - use all available space
+            let available_height = ui.available_height();
+            
+            let mut results_area = egui::ScrollArea::vertical()
+                .id_salt("results")
+                .max_height(available_height)
+                .auto_shrink([false, false]);
+            if let Some(offset) = self.restore_scroll.take() {
+                results_area = results_area.vertical_scroll_offset(offset);
+            }
+            let output = results_area.show(ui, |ui| {
+                self.render_directory_tree(ui, 0);
+            });
+            if !self.is_scanning {
+                self.results_scroll_offset = output.state.offset.y;
+            }
+        } else if !self.is_scanning {
+            ui.add_space(8.0);
+            ui.label(egui::RichText::new("No results yet. Pick what to look for under Settings, then scan.")
+                .size(12.0)
+                .color(egui::Color32::from_rgb(120, 120, 120)));
+        }
+    }
+    
+    // Everything that went wrong or was held back during the last scan or cleanup
+    fn render_log_tab(&mut self, ui: &mut egui::Ui) {
+        if self.log_entry_count() == 0 && self.dry_run_log.is_empty() && self.filtered_paths.is_empty() {
+            ui.label(egui::RichText::new("Nothing to report yet.")
+                .size(12.0)
+                .color(egui::Color32::from_rgb(120, 120, 120)));
+        }
+        
+        if !self.skipped_large_dirs.is_empty() {
+            ui.add_space(4.0);
+            self.render_skipped_large_dirs(ui);
+        }
+        
+        if !self.scan_errors.is_empty() {
+            ui.add_space(4.0);
+            egui::CollapsingHeader::new(
+                egui::RichText::new(format!("⚠️ {} errors", self.scan_errors.len()))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(244, 67, 54))
+            )
+            .id_salt("scan_errors")
+            .show(ui, |ui| {
+                for (path, error) in &self.scan_errors {
+                    ui.label(egui::RichText::new(format!("📄 {} — {}", path, error))
+                        .size(11.0)
+                        .color(egui::Color32::from_rgb(80, 80, 80)));
+                }
+            });
+        }
+        
+        if !self.dry_run_log.is_empty() {
+            ui.add_space(4.0);
+            egui::CollapsingHeader::new(
+                egui::RichText::new(format!("👀 Dry run would remove {} files", self.dry_run_log.len()))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(33, 150, 243))
+            )
+            .id_salt("dry_run_log")
+            .show(ui, |ui| {
+                for path in &self.dry_run_log {
+                    ui.label(egui::RichText::new(format!("📄 {}", path.display()))
+                        .size(11.0)
+                        .color(egui::Color32::from_rgb(80, 80, 80)));
+                }
+            });
+        }
+        
+        if !self.archive_errors.is_empty() {
+            ui.add_space(4.0);
+            egui::CollapsingHeader::new(
+                egui::RichText::new(format!("⚠️ {} files could not be archived", self.archive_errors.len()))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(244, 67, 54))
+            )
+            .id_salt("archive_errors")
+            .show(ui, |ui| {
+                for (path, error) in &self.archive_errors {
+                    ui.label(egui::RichText::new(format!("📄 {} — {}", path, error))
+                        .size(11.0)
+                        .color(egui::Color32::from_rgb(80, 80, 80)));
+                }
+            });
+        }
+        
+        if !self.filtered_paths.is_empty() {
+            ui.add_space(4.0);
+            egui::CollapsingHeader::new(
+                egui::RichText::new(format!("🔎 The smart filter left out {} files", self.filtered_paths.len()))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(100, 100, 100))
+            )
+            .id_salt("filtered_paths")
+            .show(ui, |ui| {
+                // Can run to many thousands, so only the visible rows are laid out
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                egui::ScrollArea::vertical()
+                    .id_salt("filtered_paths_list")
+                    .max_height(300.0)
+                    .show_rows(ui, row_height, self.filtered_paths.len(), |ui, rows| {
+                        for (path, category) in &self.filtered_paths[rows] {
+                            ui.label(egui::RichText::new(format!("📄 {} — {}", path.display(), category.label()))
+                                .size(11.0)
+                                .color(egui::Color32::from_rgb(80, 80, 80)));
+                        }
+                    });
+            });
+        }
+    }
+    
+    fn log_entry_count(&self) -> usize {
+        self.skipped_large_dirs.len() + self.scan_errors.len() + self.archive_errors.len()
+    }
+    
+    fn render_trash_report(&mut self, ui: &mut egui::Ui) {
+        if let Some(error) = &self.trash_error {
+            ui.label(egui::RichText::new(format!("⚠️ {}", error))
+                .size(11.0)
+                .color(egui::Color32::from_rgb(244, 67, 54)));
+        } else if self.trash_volumes.is_empty() {
+            ui.label(egui::RichText::new("Trash is empty.")
+                .size(11.0)
+                .color(egui::Color32::from_rgb(80, 80, 80)));
+        }
+        
+        for volume in &self.trash_volumes {
+            ui.label(egui::RichText::new(format!(
+                    "📦 {}  —  {} ({} items)",
+                    volume.location, human_size(volume.size_bytes), volume.item_count))
+                .size(11.0)
+                .color(egui::Color32::from_rgb(80, 80, 80)));
+        }
+        
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            let refresh_btn = egui::Button::new(
+                egui::RichText::new("🔄 Refresh").size(12.0).color(egui::Color32::WHITE)
+            )
+            .fill(egui::Color32::from_rgb(33, 150, 243))
+            .rounding(egui::Rounding::same(3.0))
+            .min_size(egui::vec2(80.0, 24.0));
+            
+            if ui.add(refresh_btn).clicked() {
+                self.refresh_trash();
+            }
+            
+            let has_items = self.trash_volumes.iter().any(|v| v.item_count > 0);
+            let empty_btn = egui::Button::new(
+                egui::RichText::new("Empty Trash").size(12.0).color(egui::Color32::WHITE)
+            )
+            .fill(egui::Color32::from_rgb(244, 67, 54))
+            .rounding(egui::Rounding::same(3.0))
+            .min_size(egui::vec2(90.0, 24.0));
+            
+            if ui.add_enabled(has_items, empty_btn).clicked() {
+                self.confirm_empty_trash_open = true;
+            }
+        });
+    }
+    
+    fn render_filter_rules(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(egui::RichText::new("Edit filter rules").size(12.0))
+            .id_salt("filter_rules")
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new("One pattern per line, matched against file names ignoring case.")
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(120, 120, 120)));
+                ui.add_space(4.0);
+                
+                let rules = &mut self.filter_rules;
+                ui.columns(3, |columns| {
+                    pattern_list_editor(&mut columns[0], "Binary extensions (ending)", &mut rules.binary_extensions);
+                    pattern_list_editor(&mut columns[1], "System and cache (anywhere)", &mut rules.system_patterns);
+                    pattern_list_editor(&mut columns[2], "Build folders (anywhere)", &mut rules.build_patterns);
+                });
+                
+                ui.add_space(4.0);
+                if ui.button(egui::RichText::new("Reset to defaults").size(12.0)).clicked() {
+                    self.filter_rules = FilterRules::default();
+                }
+            });
+    }
+    
+    fn render_empty_trash_dialog(&mut self, ctx: &egui::Context) {
+        let total_bytes: u64 = self.trash_volumes.iter().map(|v| v.size_bytes).sum();
+        let total_items: usize = self.trash_volumes.iter().map(|v| v.item_count).sum();
+        
+        egui::Window::new("Empty Trash?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Permanently delete {} items ({}) from the Trash?",
+                    total_items, human_size(total_bytes)));
+                ui.label(egui::RichText::new("This cannot be undone.")
+                    .color(egui::Color32::from_rgb(244, 67, 54)));
+                ui.add_space(8.0);
+                
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        self.confirm_empty_trash_open = false;
+                    }
+                    
+                    let confirm_btn = egui::Button::new(
+                        egui::RichText::new("Empty Trash").color(egui::Color32::WHITE)
+                    )
+                    .fill(egui::Color32::from_rgb(244, 67, 54));
+                    
+                    if ui.add(confirm_btn).clicked() {
+                        self.confirm_empty_trash_open = false;
+                        self.status_message = match trash_bin::empty_trash() {
+                            Ok(()) => format!("✅ Emptied Trash, freed {}.", human_size(total_bytes)),
+                            Err(e) => format!("❌ Could not empty Trash: {}", e),
+                        };
+                        self.refresh_trash();
+                        self.refresh_drive_space();
+                    }
+                });
+            });
+    }
+    
+    fn render_confirm_delete_dialog(&mut self, ctx: &egui::Context) {
+        self.refresh_delete_estimate();
+        let scope = self.confirm_delete_scope.clone();
+        let selected_count = self.scan_results.iter().filter(|r| scope.includes(r)).count();
+        let selected_bytes = self.scope_bytes(&scope);
+        let marked_folders = if scope.takes_marked_folders() { self.marked_folders.len() } else { 0 };
+        // Whole folders can hold far more than the list shows, so they always need the extra tick
+        let large = selected_count >= LARGE_DELETE_FILES
+            || selected_bytes >= LARGE_DELETE_BYTES
+            || marked_folders > 0;
+        let network_count = self.scan_results.iter()
+            .filter(|r| scope.includes(r) && r.volume == Some(VolumeKind::Network))
+            .count();
+        let title = if self.permanent_delete { "Delete files?" } else { "Move files to Trash?" };
+        
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                if self.permanent_delete {
+                    ui.label(format!(
+                        "Permanently delete {} files ({})?",
+                        selected_count, human_size(selected_bytes)));
+                    ui.label(egui::RichText::new("This cannot be undone.")
+                        .color(egui::Color32::from_rgb(244, 67, 54)));
+                    if self.secure_delete {
+                        ui.label(egui::RichText::new("🔏 Each file is overwritten with zeros first, which can take a while.")
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(80, 80, 80)));
+                    }
+                } else {
+                    ui.label(format!(
+                        "Move {} files ({}) to the Trash?",
+                        selected_count, human_size(selected_bytes)));
+                }
+                match &scope {
+                    DeleteScope::Selected => {}
+                    DeleteScope::Failed => {
+                        ui.label(egui::RichText::new("🔁 These are the files the last delete couldn't remove, whether or not they're selected.")
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(80, 80, 80)));
+                    }
+                    DeleteScope::Zipped(_) => {
+                        ui.label(egui::RichText::new("🗜️ These are the originals of the files that were just zipped.")
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(80, 80, 80)));
+                    }
+                }
+                
+                let payoff = if self.permanent_delete {
+                    format!("💾 This will free ~{}.", human_size(self.confirm_free_bytes))
+                } else {
+                    format!("💾 This will free ~{} once the Trash is emptied.", human_size(self.confirm_free_bytes))
+                };
+                ui.label(egui::RichText::new(payoff)
+                    .size(13.0)
+                    .strong()
+                    .color(egui::Color32::from_rgb(46, 125, 50)))
+                    .on_hover_text("The selected files, the associated files that go with them and any whole folders");
+                
+                if !self.permanent_delete && network_count > 0 {
+                    ui.add_space(4.0);
+                    ui.label(egui::RichText::new(format!(
+                        "🌐 {} of these are on a network drive. They don't go to this computer's \
+                        Trash and may be deleted for good.", network_count))
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(255, 152, 0)));
+                }
+                
+                if marked_folders > 0 {
+                    ui.add_space(4.0);
+                    ui.label(egui::RichText::new(format!(
+                        "🗂 {} folders go whole, with everything in them, including files the scan didn't list:",
+                        self.marked_folders.len()))
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(244, 67, 54)));
+                    egui::ScrollArea::vertical().id_salt("confirm_folders").max_height(100.0).show(ui, |ui| {
+                        for folder in &self.marked_folders {
+                            ui.label(egui::RichText::new(format!("📂 {}", folder.display()))
+                                .size(11.0)
+                                .color(egui::Color32::from_rgb(80, 80, 80)));
+                        }
+                    });
+                }
+                
+                if !self.confirm_associated.is_empty() {
+                    ui.add_space(4.0);
+                    egui::CollapsingHeader::new(
+                        egui::RichText::new(format!(
+                            "🧩 Also removes {} associated files", self.confirm_associated.len()))
+                            .size(12.0)
+                    )
+                    .id_salt("confirm_associated")
+                    .show(ui, |ui| {
+                        egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                            for path in &self.confirm_associated {
+                                ui.label(egui::RichText::new(format!("📄 {}", path.display()))
+                                    .size(11.0)
+                                    .color(egui::Color32::from_rgb(80, 80, 80)));
+                            }
+                        });
+                    });
+                }
+                
+                if large {
+                    ui.add_space(6.0);
+                    let acknowledgement = if self.permanent_delete {
+                        "I understand this is permanent"
+                    } else {
+                        "I've checked that these files can go"
+                    };
+                    ui.checkbox(&mut self.confirm_delete_acknowledged, acknowledgement);
+                }
+                ui.add_space(8.0);
+                
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        self.confirm_delete_open = false;
+                    }
+                    
+                    let confirm_label = if self.dry_run { "Preview" } else { "Delete" };
+                    let confirm_btn = egui::Button::new(
+                        egui::RichText::new(confirm_label).color(egui::Color32::WHITE)
+                    )
+                    .fill(egui::Color32::from_rgb(244, 67, 54));
+                    
+                    if ui.add_enabled(!large || self.confirm_delete_acknowledged, confirm_btn).clicked() {
+                        self.confirm_delete_open = false;
+                        self.start_deletion(!self.permanent_delete, &scope);
+                    }
+                });
+            });
+    }
+    
+    fn render_external_scan_dialog(&mut self, ctx: &egui::Context) {
+        let Some(directories) = &self.external_scan_warning else {
+            return;
+        };
+        let mut scan_anyway = false;
+        let mut cancel = false;
+        
+        egui::Window::new("Scan external drives?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label("These folders aren't on a local disk:");
+                for (directory, kind) in directories {
+                    ui.label(egui::RichText::new(format!("{} {} ({})", kind.icon(), directory, kind.label()))
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(80, 80, 80)));
+                }
+                ui.add_space(4.0);
+                ui.label(egui::RichText::new("Scanning them can be slow and keep the connection busy. \
+                    Files deleted from a network drive don't go to this computer's Trash.")
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(100, 100, 100)));
+                ui.add_space(8.0);
+                
+                ui.horizontal(|ui| {
+                    cancel = ui.button("Cancel").clicked();
+                    
+                    let scan_btn = egui::Button::new(
+                        egui::RichText::new("Scan anyway").color(egui::Color32::WHITE)
+                    )
+                    .fill(egui::Color32::from_rgb(33, 150, 243));
+                    scan_anyway = ui.add(scan_btn).clicked();
+                });
+            });
+        
+        // Confirmed folders aren't asked about again, here or after a restart
+        if scan_anyway {
+            if let Some(directories) = self.external_scan_warning.take() {
+                self.confirmed_external_directories.extend(directories.into_iter().map(|(directory, _)| directory));
+            }
+            self.scan_files();
+        } else if cancel {
+            self.external_scan_warning = None;
+        }
+    }
+    
+    fn render_restore_dialog(&mut self, ctx: &egui::Context) {
+        let Some(cache) = &self.pending_restore else {
+            return;
+        };
+        let saved_at = chrono::DateTime::parse_from_rfc3339(&cache.saved_at)
+            .map_or_else(|_| cache.saved_at.clone(), |time| time.format("%Y-%m-%d %H:%M").to_string());
+        let selected = cache.results.iter().filter(|r| r.should_delete).count();
+        let mut restore = false;
+        let mut discard = false;
+        
+        egui::Window::new("Restore previous scan?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(format!("The scan from {} found {} files, {} of them selected.",
+                    saved_at, cache.results.len(), selected));
+                for directory in &cache.directories {
+                    ui.label(egui::RichText::new(format!("📁 {}", directory.display()))
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(80, 80, 80)));
+                }
+                ui.add_space(4.0);
+                ui.label(egui::RichText::new("Files deleted or moved since then are left out.")
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(100, 100, 100)));
+                ui.add_space(8.0);
+                
+                ui.horizontal(|ui| {
+                    discard = ui.button("Discard").clicked();
+                    
+                    let restore_btn = egui::Button::new(
+                        egui::RichText::new("Restore").color(egui::Color32::WHITE)
+                    )
+                    .fill(egui::Color32::from_rgb(33, 150, 243));
+                    restore = ui.add(restore_btn).clicked();
+                });
+            });
+        
+        if restore {
+            if let Some(mut cache) = self.pending_restore.take() {
+                let dropped = cache.revalidate();
+                self.status_message = format!("📂 Restored {} files from the previous scan.", cache.results.len());
+                if dropped > 0 {
+                    self.status_message += &format!(" {} no longer exist and were left out.", dropped);
+                }
+                self.scan_roots = cache.directories;
+                self.scan_results = cache.results;
+                self.marked_folders.clear();
+                self.result_tree = None;
+                self.selected_tab = Tab::Results;
+                self.refresh_drive_space();
+            }
+        } else if discard {
+            self.pending_restore = None;
+            scan_cache::clear();
+        }
+    }
+    
+    // Offers what a background scan found without replacing the results on screen
+    // until the user asks for it
+    fn render_scheduled_results(&mut self, ui: &mut egui::Ui) {
+        let Some(found) = &self.scheduled_results else {
+            return;
+        };
+        let finished_at = chrono::DateTime::<chrono::Local>::from(found.finished_at).format("%Y-%m-%d %H:%M");
+        let bytes = found.results.iter().map(|r| r.size_bytes).sum();
+        let mut show = false;
+        let mut dismiss = false;
+        
+        let banner_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(227, 242, 253))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(33, 150, 243)))
+            .inner_margin(egui::Margin::same(8.0))
+            .rounding(egui::Rounding::same(4.0));
+        
+        banner_frame.show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("🕒 The scheduled scan at {} found {} files ({})",
+                        finished_at, found.results.len(), human_size(bytes)))
+                    .size(12.0)
+                    .color(egui::Color32::BLACK));
+                
+                let show_btn = egui::Button::new(
+                    egui::RichText::new("Show").color(egui::Color32::WHITE)
+                )
+                .fill(egui::Color32::from_rgb(33, 150, 243))
+                .rounding(egui::Rounding::same(3.0));
+                show = ui.add(show_btn).clicked();
+                dismiss = ui.button("Dismiss").clicked();
+            });
+        });
+        ui.add_space(4.0);
+        
+        if show {
+            if let Some(found) = self.scheduled_results.take() {
+                self.status_message = format!("🕒 Showing the {} files from the scheduled scan.", found.results.len());
+                self.scan_roots = found.directories;
+                self.scan_results = found.results;
+                self.marked_folders.clear();
+                self.result_tree = None;
+                self.refresh_drive_space();
+            }
+        } else if dismiss {
+            self.scheduled_results = None;
+        }
+    }
+    
+    // Only finished scans of real folders are worth coming back to. A restore offer
+    // that was never answered is kept for next time. This runs as the window
+    // closes, so a save that fails just means no offer on the next launch.
+    fn save_scan_cache(&self) {
+        if self.pending_restore.is_some() {
+            return;
+        }
+        if self.is_scanning || self.sandbox_root.is_some() || self.scan_results.is_empty() {
+            scan_cache::clear();
+        } else {
+            let _ = scan_cache::save(&self.scan_roots, &self.scan_results);
+        }
+    }
+    
+    fn render_history_window(&mut self, ctx: &egui::Context) {
+        let Some(lines) = &self.history_lines else {
+            return;
+        };
+        
+        let mut open = true;
+        egui::Window::new("📜 Deletion History")
+            .open(&mut open)
+            .default_size(egui::vec2(640.0, 400.0))
+            .show(ctx, |ui| {
+                if lines.is_empty() {
+                    ui.label(egui::RichText::new("Nothing has been deleted yet.")
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(120, 120, 120)));
+                    return;
+                }
+                
+                // Only the visible rows are laid out, the log can get long
+                let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                egui::ScrollArea::both().show_rows(ui, row_height, lines.len(), |ui, rows| {
+                    for line in &lines[rows] {
+                        ui.label(egui::RichText::new(line).monospace());
+                    }
+                });
+            });
+        
+        if !open {
+            self.history_lines = None;
+        }
+    }
+    
+    // Version and platform, in a form that can go straight into a bug report
+    fn render_about_window(&mut self, ctx: &egui::Context) {
+        let version_info = format!("PinnacleSort {} ({} {})",
+            env!("CARGO_PKG_VERSION"), std::env::consts::OS, std::env::consts::ARCH);
+        let mut open_error = None;
+        
+        egui::Window::new("ℹ️ About PinnacleSort")
+            .open(&mut self.about_open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(&version_info).size(14.0).strong());
+                ui.label(egui::RichText::new(env!("CARGO_PKG_DESCRIPTION"))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(80, 80, 80)));
+                ui.add_space(8.0);
+                
+                ui.horizontal(|ui| {
+                    if ui.button("📋 Copy version info").clicked() {
+                        ui.ctx().copy_text(version_info.clone());
+                    }
+                    if ui.button("🔗 Source and issues").on_hover_text(env!("CARGO_PKG_REPOSITORY")).clicked() {
+                        open_error = opener::open(env!("CARGO_PKG_REPOSITORY")).err();
+                    }
+                });
+            });
+        
+        if let Some(e) = open_error {
+            self.status_message = format!("❌ Could not open the project page: {}", e);
+        }
+    }
+    
+    fn refresh_drive_space(&mut self) {
+        let directories: Vec<PathBuf> = self.settings().search_directories()
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        self.drive_space = volumes::drive_space(&directories);
+    }
+    
+    // One bar per drive, turning red once it's over 90% full
+    fn render_drive_space(&self, ui: &mut egui::Ui) {
+        for drive in &self.drive_space {
+            let used_bytes = drive.total_bytes.saturating_sub(drive.available_bytes);
+            let fraction = used_bytes as f32 / drive.total_bytes.max(1) as f32;
+            let fill = if fraction > 0.9 {
+                egui::Color32::from_rgb(244, 67, 54)
+            } else {
+                egui::Color32::from_rgb(33, 150, 243)
+            };
+            
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("💽 {}", drive.root.display()))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(80, 80, 80)));
+                ui.add(egui::ProgressBar::new(fraction)
+                    .fill(fill)
+                    .desired_height(16.0)
+                    .text(egui::RichText::new(format!(
+                        "{} free of {}", human_size(drive.available_bytes), human_size(drive.total_bytes)))
+                        .size(11.0)
+                        .color(egui::Color32::WHITE)))
+                    .on_hover_text("Files moved to the Trash still take up space until it's emptied");
+            });
+        }
+    }
+    
+    fn refresh_trash(&mut self) {
+        match trash_bin::query_trash() {
+            Ok(volumes) => {
+                self.trash_volumes = volumes;
+                self.trash_error = None;
+            }
+            Err(e) => {
+                self.trash_volumes.clear();
+                self.trash_error = Some(e);
+            }
+        }
+    }
+    
+    fn preset_values(preset: Preset) -> (u64, bool, bool, bool, usize) {
+        // (days, smart filter, pre-select, usage signal, large directory threshold)
+        match preset {
+            Preset::Conservative => (180, true, false, true, 5_000),
+            Preset::Balanced => (14, true, true, false, 10_000),
+            Preset::Aggressive => (7, false, true, false, 100_000),
+            // The junk list takes the smart filter's place
+            Preset::Junk => (30, false, true, false, 100_000),
+        }
+    }
+    
+    fn settings(&self) -> Settings {
+        Settings {
+            time_limit: self.time_limit,
+            time_unit: self.time_unit,
+            time_basis: self.time_basis,
+            time_combiner: self.time_combiner,
+            downloads_enabled: self.downloads_enabled,
+            documents_enabled: self.documents_enabled,
+            desktop_enabled: self.desktop_enabled,
+            pictures_enabled: self.pictures_enabled,
+            videos_enabled: self.videos_enabled,
+            music_enabled: self.music_enabled,
+            trash_scan_enabled: self.trash_scan_enabled,
+            custom_directories: self.custom_directories.clone(),
+            exclude_patterns: self.exclude_patterns.clone(),
+            respect_gitignore: self.respect_gitignore,
+            protected_paths: self.protected_paths.clone(),
+            never_delete: self.never_delete.clone(),
+            never_delete_patterns: self.never_delete_patterns.clone(),
+            confirmed_external_directories: self.confirmed_external_directories.clone(),
+            archive_destination: self.archive_destination.clone(),
+            zip_remove_originals: self.zip_remove_originals,
+            home_directory: self.home_directory.clone(),
+            history_max_kb: self.history_max_kb,
+            scheduled_scan_days: self.scheduled_scan_days,
+            last_scheduled_scan: self.last_scheduled_scan,
+            associated_trigger_extension: self.associated_trigger_extension.clone(),
+            associated_extensions: self.associated_extensions.clone(),
+            smart_filter_enabled: self.smart_filter_enabled,
+            filter_rules: self.filter_rules.clone(),
+            junk_patterns: self.junk_patterns.clone(),
+            preset: self.preset,
+            preselect_results: self.preselect_results,
+            usage_signal_enabled: self.usage_signal_enabled,
+            large_dir_threshold: self.large_dir_threshold,
+            follow_symlinks: self.follow_symlinks,
+            include_hidden: self.include_hidden,
+            max_depth: self.max_depth,
+            protect_modified_days: self.protect_modified_days,
+            duplicates_enabled: self.duplicates_enabled,
+            min_size_mb: self.min_size_mb,
+            max_size_mb: self.max_size_mb,
+            include_extensions: self.include_extensions.clone(),
+            date_display: self.date_display,
+            date_format: self.date_format.clone(),
+            window_size: self.window_size,
+            window_position: self.window_position,
+            expanded_folders: self.expanded_folders.clone(),
+        }
+    }
+    
+    fn apply_settings(&mut self, settings: Settings) {
+        self.time_limit = settings.time_limit;
+        self.time_unit = settings.time_unit;
+        self.time_basis = settings.time_basis;
+        self.time_combiner = settings.time_combiner;
+        self.downloads_enabled = settings.downloads_enabled;
+        self.documents_enabled = settings.documents_enabled;
+        self.desktop_enabled = settings.desktop_enabled;
+        self.pictures_enabled = settings.pictures_enabled;
+        self.videos_enabled = settings.videos_enabled;
+        self.music_enabled = settings.music_enabled;
+        self.trash_scan_enabled = settings.trash_scan_enabled;
+        self.custom_directories = settings.custom_directories.clone();
+        self.exclude_patterns = settings.exclude_patterns.clone();
+        self.respect_gitignore = settings.respect_gitignore;
+        self.protected_paths = settings.protected_paths.clone();
+        self.never_delete = settings.never_delete.clone();
+        self.never_delete_patterns = settings.never_delete_patterns.clone();
+        self.confirmed_external_directories = settings.confirmed_external_directories.clone();
+        self.archive_destination = settings.archive_destination.clone();
+        self.zip_remove_originals = settings.zip_remove_originals;
+        self.home_directory = settings.home_directory.clone();
+        self.history_max_kb = settings.history_max_kb;
+        self.scheduled_scan_days = settings.scheduled_scan_days;
+        self.last_scheduled_scan = settings.last_scheduled_scan;
+        self.associated_trigger_extension = settings.associated_trigger_extension.clone();
+        self.associated_extensions = settings.associated_extensions.clone();
+        self.smart_filter_enabled = settings.smart_filter_enabled;
+        self.filter_rules = settings.filter_rules.clone();
+        self.junk_patterns = settings.junk_patterns.clone();
+        self.preset = settings.preset;
+        self.preselect_results = settings.preselect_results;
+        self.usage_signal_enabled = settings.usage_signal_enabled;
+        self.large_dir_threshold = settings.large_dir_threshold;
+        self.follow_symlinks = settings.follow_symlinks;
+        self.include_hidden = settings.include_hidden;
+        self.max_depth = settings.max_depth;
+        self.protect_modified_days = settings.protect_modified_days;
+        self.duplicates_enabled = settings.duplicates_enabled;
+        self.min_size_mb = settings.min_size_mb;
+        self.max_size_mb = settings.max_size_mb;
+        self.include_extensions = settings.include_extensions.clone();
+        self.date_display = settings.date_display;
+        self.date_format = settings.date_format.clone();
+        self.window_size = settings.window_size;
+        self.window_position = settings.window_position;
+        self.expanded_folders = settings.expanded_folders.clone();
+        self.saved_settings = settings;
+    }
+    
+    // Remembers the window's normal size and position. Maximized or fullscreen
+    // geometry isn't kept, and nothing is recorded mid-drag so a resize is saved once.
+    fn track_window_geometry(&mut self, ctx: &egui::Context) {
+        let (viewport, dragging) = ctx.input(|i| (i.viewport().clone(), i.pointer.any_down()));
+        if dragging
+            || viewport.minimized == Some(true)
+            || viewport.maximized == Some(true)
+            || viewport.fullscreen == Some(true)
+        {
+            return;
+        }
+        
+        if let Some(inner) = viewport.inner_rect {
+            self.window_size = Some([inner.width().round(), inner.height().round()]);
+        }
+        // Not every platform reports a window position (Wayland doesn't)
+        if let Some(outer) = viewport.outer_rect {
+            self.window_position = Some([outer.min.x.round(), outer.min.y.round()]);
+        }
+    }
+    
+    // Written as soon as anything changes, so a crash doesn't lose the last edit.
+    // Returns whether anything had changed.
+    fn save_settings_if_changed(&mut self) -> bool {
+        let settings = self.settings();
+        if settings == self.saved_settings {
+            return false;
+        }
+        
+        if let Err(e) = settings::save(&settings) {
+            self.status_message = format!("❌ Could not save settings: {}", e);
+        }
+        self.saved_settings = settings;
+        true
+    }
+    
+    // (Re)starts watching the enabled directories, unless it's already watching exactly those
+    fn start_live_watcher(&mut self, ctx: &egui::Context) {
+        let directories: Vec<PathBuf> = self.settings().search_directories()
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        if self.live_watcher.as_ref().is_some_and(|watcher| watcher.directories == directories) {
+            return;
+        }
+        
+        // The old watcher has to go first, or both would be watching for a moment
+        self.live_watcher = None;
+        let ctx = ctx.clone();
+        match watcher::LiveWatcher::start(directories, move || ctx.request_repaint()) {
+            Ok(watcher) => self.live_watcher = Some(watcher),
+            Err(e) => {
+                self.live_enabled = false;
+                self.status_message = format!("❌ Could not watch for changes: {}", e);
+            }
+        }
+    }
+    
+    // Starts, restarts or stops the scheduled scans and the tray icon to match the
+    // settings, and picks up whatever the last scheduled scan found
+    fn sync_schedule(&mut self, ctx: &egui::Context) {
+        let Some(interval_days) = self.scheduled_scan_days else {
+            self.scan_schedule = None;
+            self.tray = None;
+            self.last_scheduled_scan = None;
+            return;
+        };
+        
+        if self.scan_schedule.as_ref().is_none_or(|schedule| schedule.interval_days != interval_days) {
+            // Turning them on counts as a scan, so the first one is a full interval away
+            let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+            let last_run = std::time::UNIX_EPOCH + std::time::Duration::from_secs(*self.last_scheduled_scan.get_or_insert(now));
+            self.scan_schedule = Some(schedule::ScanSchedule::start(interval_days, last_run, ctx.clone()));
+        }
+        if tray::SUPPORTED && self.tray.is_none() {
+            match tray::Tray::new(load_icon(), ctx) {
+                Ok(tray) => self.tray = Some(tray),
+                Err(e) => {
+                    self.scheduled_scan_days = None;
+                    self.status_message = format!("❌ Could not add the tray icon: {}", e);
+                }
+            }
+        }
+        
+        if let Some(found) = self.scan_schedule.as_ref().and_then(|schedule| schedule.try_recv()) {
+            self.last_scheduled_scan = Some(found.finished_at
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs());
+            if !found.results.is_empty() {
+                self.scheduled_results = Some(found);
+                self.selected_tab = Tab::Results;
+            }
+        }
+    }
+    
+    fn stop_live_watcher(&mut self) {
+        self.live_watcher = None;
+        self.live_changed_at = None;
+    }
+    
+    // Rescans once changes have settled, but never in the middle of a scan or while
+    // a dialog is waiting on the current results
+    fn poll_live_watcher(&mut self, ctx: &egui::Context) {
+        let Some(watcher) = &self.live_watcher else {
+            return;
+        };
+        if watcher.take_changed() {
+            self.live_changed_at = Some(std::time::Instant::now());
+        }
+        
+        let Some(changed_at) = self.live_changed_at else {
+            return;
+        };
+        let quiet = changed_at.elapsed();
+        if quiet < LIVE_DEBOUNCE {
+            ctx.request_repaint_after(LIVE_DEBOUNCE - quiet);
+            return;
+        }
+        if self.is_scanning || self.is_busy() || self.confirm_delete_open || self.quick_clean.is_some() {
+            return;
+        }
+        
+        self.live_changed_at = None;
+        self.scan_files();
+    }
+    
+    fn apply_preset(&mut self, preset: Preset) {
+        let (days, smart_filter, preselect, usage_signal, large_dir_threshold) =
+            Self::preset_values(preset);
+        
+        self.preset = preset;
+        self.time_limit = days;
+        self.time_unit = TimeUnit::Days;
+        self.smart_filter_enabled = smart_filter;
+        self.preselect_results = preselect;
+        self.usage_signal_enabled = usage_signal;
+        self.large_dir_threshold = large_dir_threshold;
+    }
+    
+    fn matches_preset(&self) -> bool {
+        self.time_unit == TimeUnit::Days && Self::preset_values(self.preset) == (
+            self.time_limit,
+            self.smart_filter_enabled,
+            self.preselect_results,
+            self.usage_signal_enabled,
+            self.large_dir_threshold,
+        )
+    }
+    
+    fn start_quick_clean(&mut self) {
+        let downloads = settings::user_dir(&self.home_directory, directories::UserDirs::download_dir, "Downloads");
+        
+        // Run the regular scan over Downloads only, with the conservative filters forced on
+        let options = ScanOptions {
+            time_limit: std::time::Duration::from_secs(60 * 60 * 24 * QUICK_CLEAN_DAYS),
+            smart_filter_enabled: true,
+            preselect_results: true,
+            usage_signal_enabled: false,
+            include_extensions: Vec::new(),
+            junk: None,
+            ..self.scan_options()
+        };
+        
+        self.discard_sandbox();
+        self.scan_results.clear();
+        self.result_tree = None;
+        self.skipped_large_dirs.clear();
+        self.scan_errors.clear();
+        self.filtered_paths.clear();
+        self.last_stats = None;
+        self.dry_run_log.clear();
+        self.scan_roots = vec![PathBuf::from(downloads)];
+        self.start_scan(self.scan_roots.clone(), Vec::new(), options, ScanKind::QuickClean);
+    }
+    
+    fn render_quick_clean_dialog(&mut self, ctx: &egui::Context) {
+        let Some(stage) = self.quick_clean else {
+            return;
+        };
+        
+        let mut close = false;
+        let mut confirm = None;
+        
+        egui::Window::new("✨ Quick Clean")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                match stage {
+                    QuickClean::Review { .. } if self.scan_results.is_empty() => {
+                        ui.label(format!(
+                            "Your Downloads folder is already tidy — nothing untouched for {} days.",
+                            QUICK_CLEAN_DAYS));
+                        ui.add_space(8.0);
+                        close = ui.button("Close").clicked();
+                    }
+                    QuickClean::Review { before_bytes, found_bytes } => {
+                        ui.label(format!(
+                            "Found {} files ({}) in Downloads that you haven't opened in {} days.",
+                            self.scan_results.len(), human_size(found_bytes), QUICK_CLEAN_DAYS));
+                        ui.label("They will be moved to the Trash, so you can still get them back.");
+                        ui.add_space(8.0);
+                        
+                        ui.horizontal(|ui| {
+                            let trash_btn = egui::Button::new(
+                                egui::RichText::new("Move to Trash").color(egui::Color32::WHITE)
+                            )
+                            .fill(egui::Color32::from_rgb(76, 175, 80));
+                            
+                            if ui.add(trash_btn).clicked() {
+                                confirm = Some(before_bytes);
+                            }
+                            // Leaves the results in the tree for a closer look
+                            if ui.button("Let me review first").clicked() {
+                                close = true;
+                            }
+                            if ui.button("Cancel").clicked() {
+                                close = true;
+                                self.scan_results.clear();
+                                self.result_tree = None;
+                            }
+                        });
+                    }
+                    QuickClean::Deleting { .. } => {
+                        if let Some(deletion) = &self.deletion {
+                            ui.label(format!("Moving {} files to the Trash...", deletion.total));
+                            self.render_delete_progress(ui);
+                        }
+                    }
+                    QuickClean::Done { before_bytes, after_bytes } => {
+                        ui.label(egui::RichText::new(format!(
+                                "Freed {}", human_size(before_bytes.saturating_sub(after_bytes))))
+                            .size(18.0)
+                            .strong());
+                        ui.label(format!(
+                            "Downloads: {} before  →  {} now",
+                            human_size(before_bytes), human_size(after_bytes)));
+                        if self.dry_run {
+                            ui.label(egui::RichText::new("[DRY RUN] Nothing was actually removed.")
+                                .color(egui::Color32::from_rgb(33, 150, 243)));
+                        }
+                        ui.add_space(8.0);
+                        close = ui.button("Done").clicked();
+                    }
+                }
+            });
+        
+        if let Some(before_bytes) = confirm {
+            self.start_deletion(true, &DeleteScope::Selected);
+            // Nothing started if a protected file was selected
+            self.quick_clean = self.deletion.is_some().then_some(QuickClean::Deleting { before_bytes });
+        } else if close {
+            self.quick_clean = None;
+        }
+    }
+    
+    fn render_scan_progress(&self, ui: &mut egui::Ui) {
+        // The total only exists once the counting pass is done
+        let Some(fraction) = self.scan_progress.fraction() else {
+            ui.spinner();
+            ui.label(egui::RichText::new("Counting files...")
+                .size(11.0)
+                .color(egui::Color32::from_rgb(80, 80, 80)));
+            return;
+        };
+        
+        let processed = self.scan_progress.processed.load(std::sync::atomic::Ordering::Relaxed);
+        let total = self.scan_progress.total.load(std::sync::atomic::Ordering::Relaxed);
+        ui.add(egui::ProgressBar::new(fraction)
+            .desired_width(220.0)
+            .fill(egui::Color32::from_rgb(33, 150, 243))
+            .text(format!("{} / {} files, {} found", processed.min(total), total, self.scan_results.len())));
+    }
+    
+    // Laid out right to left, so Cancel comes first
+    fn render_delete_progress(&self, ui: &mut egui::Ui) {
+        let Some(deletion) = &self.deletion else {
+            return;
+        };
+        
+        let cancel_btn = egui::Button::new(
+            egui::RichText::new("⏹ Cancel").size(12.0).color(egui::Color32::WHITE)
+        )
+        .fill(egui::Color32::from_rgb(244, 67, 54))
+        .rounding(egui::Rounding::same(3.0))
+        .min_size(egui::vec2(80.0, 24.0));
+        
+        if ui.add_enabled(!deletion.is_cancelled(), cancel_btn)
+            .on_hover_text("Stops after the file it's on. The rest stay selected.")
+            .clicked()
+        {
+            deletion.cancel();
+        }
+        ui.add(egui::ProgressBar::new(deletion.fraction())
+            .desired_width(220.0)
+            .fill(egui::Color32::from_rgb(244, 67, 54))
+            .text(format!("{} / {} files", deletion.outcomes.len(), deletion.total)));
+    }
+    
+    fn render_undo_button(&mut self, ui: &mut egui::Ui) {
+        let Some(deleted_at) = self.last_deleted_at else {
+            return;
+        };
+        
+        let elapsed = deleted_at.elapsed();
+        if elapsed >= UNDO_WINDOW {
+            self.last_deleted.clear();
+            self.last_deleted_at = None;
+            return;
+        }
+        // Make sure the button goes away on time even if nothing else repaints
+        ui.ctx().request_repaint_after(UNDO_WINDOW - elapsed);
+        
+        let undo_btn = egui::Button::new(
+            egui::RichText::new(format!("↩️ Undo ({})", self.last_deleted.len()))
+                .size(12.0)
+                .color(egui::Color32::WHITE)
+        )
+        .fill(egui::Color32::from_rgb(33, 150, 243))
+        .rounding(egui::Rounding::same(3.0))
+        .min_size(egui::vec2(80.0, 24.0));
+        
+        ui.add_space(8.0);
+        let response = ui.add_enabled(trash_bin::CAN_RESTORE, undo_btn);
+        let response = if trash_bin::CAN_RESTORE {
+            response.on_hover_text("Put the files you just deleted back from the Trash")
+        } else {
+            response.on_disabled_hover_text(
+                "Restoring from the Trash isn't supported on this platform. Use Finder's \"Put Back\" instead.")
+        };
+        
+        if response.clicked() {
+            self.status_message = match trash_bin::restore(&self.last_deleted) {
+                Ok(restored) => format!("↩️ Restored {} files from the Trash.", restored),
+                Err(e) => format!("❌ Could not restore from the Trash: {}", e),
+            };
+            self.last_deleted.clear();
+            self.last_deleted_at = None;
+            
+            if self.trash_report_enabled {
+                self.refresh_trash();
+            }
+            self.refresh_drive_space();
+        }
+    }
+    
+    fn render_skipped_large_dirs(&mut self, ui: &mut egui::Ui) {
+        let mut descend_into = None;
+        let is_scanning = self.is_scanning;
+        
+        egui::CollapsingHeader::new(
+            egui::RichText::new(format!(
+                    "⚠️ Held off on {} large directories", self.skipped_large_dirs.len()))
+                .size(12.0)
+                .color(egui::Color32::from_rgb(230, 81, 0))
+        )
+        .id_salt("skipped_large_dirs")
+        .show(ui, |ui| {
+            for (idx, (path, entry_count)) in self.skipped_large_dirs.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(format!("📂 {} ({} entries)", path.display(), entry_count))
+                        .size(11.0)
+                        .color(egui::Color32::from_rgb(80, 80, 80)));
+                    
+                    let scan_btn = egui::Button::new(
+                        egui::RichText::new("Scan anyway").size(11.0).color(egui::Color32::WHITE)
+                    )
+                    .fill(egui::Color32::from_rgb(33, 150, 243))
+                    .rounding(egui::Rounding::same(2.0))
+                    .min_size(egui::vec2(80.0, 18.0));
+                    
+                    if ui.add_enabled(!is_scanning, scan_btn).clicked() {
+                        descend_into = Some(idx);
+                    }
+                });
+            }
+        });
+        
+        if let Some(idx) = descend_into {
+            let (path, _) = self.skipped_large_dirs.remove(idx);
+            let results_before = self.scan_results.len();
+            
+            self.large_dirs_allowed.insert(path.clone());
+            self.status_message = format!("Scanning {}...", path.display());
+            self.start_scan(
+                vec![path.clone()],
+                Vec::new(),
+                self.scan_options(),
+                ScanKind::Directory { path, results_before },
+            );
+        }
+    }
+    
+    fn render_directory_tree(&mut self, ui: &mut egui::Ui, _depth: usize) {
+        let cached = self.result_tree.as_ref().filter(|cached| {
+            cached.sort_key == self.sort_key
+                && cached.sort_descending == self.sort_descending
+                && cached.search_query == self.search_query
+                && cached.selection_filter == self.selection_filter
+        });
+        
+        let result_tree = match cached {
+            Some(cached) => Rc::clone(cached),
+            None => {
+                let built = Rc::new(self.build_result_tree());
+                self.result_tree = Some(Rc::clone(&built));
+                built
+            }
+        };
+        
+        if result_tree.flat.is_empty() && !self.scan_results.is_empty() {
+            let message = if self.search_query.is_empty() {
+                "No files match the selection filter.".to_string()
+            } else {
+                format!("No files match \"{}\".", self.search_query)
+            };
+            ui.label(egui::RichText::new(message)
+                .size(12.0)
+                .color(egui::Color32::from_rgb(120, 120, 120)));
+            return;
+        }
+        
+        // Folder counts only cover what's shown, so say how much is hidden
+        if result_tree.flat.len() < self.scan_results.len() {
+            ui.label(egui::RichText::new(format!(
+                    "Showing {} of {} files", result_tree.flat.len(), self.scan_results.len()))
+                .size(12.0)
+                .color(egui::Color32::from_rgb(120, 120, 120)));
+        }
+        
+        if !result_tree.flat.is_empty() {
+            self.render_top_results(ui, &result_tree.largest, &result_tree.oldest);
+            self.render_type_sizes(ui, &result_tree.by_type, &result_tree.type_sizes);
+        }
+        
+        match self.view_mode {
+            ViewMode::Tree => {}
+            ViewMode::ByType => {
+                for (extension, indices) in &result_tree.by_type {
+                    self.render_type_group(ui, extension, indices);
+                }
+                return;
+            }
+            ViewMode::Flat => {
+                ui.add_space(3.0);
+                self.render_flat_table(ui, &result_tree.flat);
+                return;
+            }
+        }
+        
+        if !result_tree.duplicate_groups.is_empty() {
+            self.render_duplicates(ui, &result_tree.duplicate_groups);
+        }
+        
+        if !result_tree.incomplete_downloads.is_empty() {
+            self.render_incomplete_downloads(ui, &result_tree.incomplete_downloads);
+        }
+        
+        // Small scans show their files straight away, and a lone root is always opened
+        let expand_all = self.scan_results.len() <= self.auto_expand_below;
+        let expand_root = expand_all || result_tree.roots.len() == 1;
+        
+        for root in &result_tree.roots {
+            self.render_tree_node(ui, root, &result_tree.tree, &result_tree.file_map, 0, expand_root);
+        }
+    }
+    
+    fn build_result_tree(&self) -> ResultTree {
+        // The files in each folder, and the folders themselves as a tree
+        let mut file_map: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut incomplete_downloads = Vec::new();
+        let mut flat = Vec::new();
+        let mut by_type: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut duplicate_groups: std::collections::BTreeMap<usize, Vec<usize>> = Default::default();
+        let mut folders = Vec::new();
+        
+        let (order, highlights) = self.search_ranked_indices();
+        
+        // Files are added in sorted order, so every directory's list comes out sorted too
+        for idx in order {
+            let result = &self.scan_results[idx];
+            
+            if !self.selection_filter.matches(result) {
+                continue;
+            }
+            
+            flat.push(idx);
+            let extension = result.file_path.extension()
+                .map(|ext| format!(".{}", ext.to_string_lossy().to_lowercase()))
+                .unwrap_or_else(|| "(no extension)".to_string());
+            by_type.entry(extension).or_default().push(idx);
+            
+            // Duplicates and incomplete downloads get their own nodes instead of
+            // sitting in the tree
+            if let Some(group) = result.duplicate_group {
+                duplicate_groups.entry(group).or_default().push(idx);
+                continue;
+            }
+            if result.incomplete_download {
+                incomplete_downloads.push(idx);
+                continue;
+            }
+            
+            let dir = result.file_path.parent()
+                .map(|p| p.to_path_buf())
+                .unwrap_or_default();
+            let files = file_map.entry(dir.to_string_lossy().to_string()).or_default();
+            if files.is_empty() {
+                folders.push(dir);
+            }
+            files.push(idx);
+        }
+        
+        let tree = folder_tree(&folders);
+        
+        // Find root directories: anything under the home folder is grouped by its
+        // top-level folder (Downloads, Documents, ...)
+        let user_home = settings::home_dir(&self.home_directory);
+        
+        let mut roots: Vec<String> = file_map.keys()
+            .map(|path| {
+                let Ok(relative) = std::path::Path::new(path).strip_prefix(&user_home) else {
+                    return path.clone();
+                };
+                match relative.components().next() {
+                    Some(top) => user_home.join(top).to_string_lossy().to_string(),
+                    None => user_home.to_string_lossy().to_string(),
+                }
+            })
+            .collect();
+        
+        roots.sort();
+        roots.dedup();
+        
+        let mut by_type: Vec<(String, Vec<usize>)> = by_type.into_iter().collect();
+        by_type.sort_by(|a, b| a.0.cmp(&b.0));
+        
+        let mut type_sizes: Vec<(usize, u64)> = by_type.iter()
+            .enumerate()
+            .map(|(group, (_, indices))| {
+                (group, indices.iter().map(|&idx| self.scan_results[idx].size_bytes).sum())
+            })
+            .collect();
+        type_sizes.sort_by_key(|&(_, bytes)| std::cmp::Reverse(bytes));
+        
+        let largest = top_results(&flat, |idx| self.scan_results[idx].size_bytes);
+        let oldest = top_results(&flat, |idx| self.scan_results[idx].days_since_access);
+        
+        ResultTree {
+            tree,
+            file_map,
+            incomplete_downloads,
+            roots,
+            flat,
+            by_type,
+            type_sizes,
+            duplicate_groups: duplicate_groups.into_values().collect(),
+            largest,
+            oldest,
+            highlights,
+            sort_key: self.sort_key,
+            sort_descending: self.sort_descending,
+            search_query: self.search_query.clone(),
+            selection_filter: self.selection_filter,
+        }
+    }
+    
+    // Without a query, every result in the chosen order. With one, only the paths
+    // it fuzzily matches, best match first and the chosen order between equals, so
+    // folders without a match never make it into the tree.
+    fn search_ranked_indices(&self) -> (Vec<usize>, HashMap<usize, Vec<usize>>) {
+        let query = self.search_query.trim();
+        if query.is_empty() {
+            return (self.sorted_indices(), HashMap::new());
+        }
+        
+        use fuzzy_matcher::FuzzyMatcher;
+        let matcher = fuzzy_matcher::skim::SkimMatcherV2::default();
+        let mut highlights = HashMap::new();
+        let mut ranked: Vec<(i64, usize)> = self.sorted_indices()
+            .into_iter()
+            .filter_map(|idx| {
+                let path = self.scan_results[idx].file_path.to_string_lossy();
+                let (score, positions) = matcher.fuzzy_indices(&path, query)?;
+                highlights.insert(idx, positions);
+                Some((score, idx))
+            })
+            .collect();
+        ranked.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        
+        (ranked.into_iter().map(|(_, idx)| idx).collect(), highlights)
+    }
+    
+    fn sorted_indices(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..self.scan_results.len()).collect();
+        let results = &self.scan_results;
+        
+        match self.sort_key {
+            SortKey::Name => indices.sort_by_cached_key(|&idx| results[idx].file_name.to_lowercase()),
+            SortKey::Size => indices.sort_by_key(|&idx| results[idx].size_bytes),
+            SortKey::DaysSinceAccess => indices.sort_by_key(|&idx| results[idx].days_since_access),
+            SortKey::Path => indices.sort_by(|&a, &b| results[a].file_path.cmp(&results[b].file_path)),
+        }
+        
+        if self.sort_descending {
+            indices.reverse();
+        }
+        indices
     }
     
     fn render_tree_node(
@@ -491,420 +3422,1666 @@ impl FileCleanerApp {
         tree: &HashMap<String, Vec<String>>,
         file_map: &HashMap<String, Vec<usize>>,
         depth: usize,
+        default_open: bool,
     ) {
         let indent = depth as f32 * 20.0;
         
         // Get folder name from path
         let folder_name = std::path::Path::new(path)
             .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or(path);
+            .map(|n| n.to_string_lossy())
+            .unwrap_or(path.into());
+        
+        // Count files in this directory and all subdirectories
+        let (total_files, selected_files) = self.count_files_recursive(path, tree, file_map);
+        
+        if total_files > 0 {
+            ui.add_space(3.0);
+            
+            // Determine icon and color based on selection state
+            let icon = if depth == 0 { "📁" } else { "📂" };
+            let selection_status = if selected_files == total_files {
+                "✅" // All selected
+            } else if selected_files > 0 {
+                "⚠️" // Partially selected
+            } else {
+                "⬜" // None selected
+            };
+            
+            let marked = self.marked_folders.contains(Path::new(path));
+            let whole_folder = if marked { "  🗂 removing whole folder" } else { "" };
+            let header_text = egui::RichText::new(
+                format!("{} {} {} ({}/{}){}", 
+                    selection_status, icon, folder_name, selected_files, total_files, whole_folder)
+            )
+            .color(egui::Color32::WHITE)
+            .size(13.0)
+            .strong();
+            
+            // Add background for the collapsing header
+            let header_fill = if marked {
+                egui::Color32::from_rgb(198, 40, 40)
+            } else {
+                egui::Color32::from_rgb(63, 81, 181)
+            };
+            let header_frame = egui::Frame::none()
+                .fill(header_fill)
+                .inner_margin(egui::Margin::symmetric(10.0, 6.0))
+                .rounding(egui::Rounding::same(2.0));
+            
+            header_frame.show(ui, |ui| {
+                // Use a stable ID for the collapsing header to maintain state
+                let response = egui::CollapsingHeader::new(header_text)
+                    .id_salt(path)
+                    .default_open(default_open || self.expanded_folders.contains(path))
+                    .show(ui, |ui| {
+                        ui.add_space(indent);
+                    
+                    // Add select/deselect buttons with color
+                    ui.horizontal(|ui| {
+                        ui.add_space(indent);
+                        
+                        let select_btn = egui::Button::new(
+                            egui::RichText::new("✓ Select All").size(12.0).color(egui::Color32::WHITE)
+                        )
+                        .fill(egui::Color32::from_rgb(76, 175, 80))
+                        .rounding(egui::Rounding::same(3.0))
+                        .min_size(egui::vec2(90.0, 25.0));
+                        
+                        if ui.add(select_btn).clicked() {
+                            self.select_all_recursive(path, tree, file_map, true);
+                        }
+                        
+                        let deselect_btn = egui::Button::new(
+                            egui::RichText::new("✗ Deselect All").size(12.0).color(egui::Color32::WHITE)
+                        )
+                        .fill(egui::Color32::from_rgb(244, 67, 54))
+                        .rounding(egui::Rounding::same(3.0))
+                        .min_size(egui::vec2(90.0, 25.0));
+                        
+                        if ui.add(deselect_btn).clicked() {
+                            self.select_all_recursive(path, tree, file_map, false);
+                        }
+                        
+                        // Only worth offering when the folder has both its own files and subfolders
+                        if file_map.contains_key(path) && tree.contains_key(path) {
+                            ui.add_space(8.0);
+                            
+                            let select_here_btn = egui::Button::new(
+                                egui::RichText::new("✓ This folder").size(12.0).color(egui::Color32::WHITE)
+                            )
+                            .fill(egui::Color32::from_rgb(129, 199, 132))
+                            .rounding(egui::Rounding::same(3.0))
+                            .min_size(egui::vec2(90.0, 25.0));
+                            
+                            if ui.add(select_here_btn).on_hover_text("Select files in this folder, not its subfolders").clicked() {
+                                self.select_in_folder(path, file_map, true);
+                            }
+                            
+                            let deselect_here_btn = egui::Button::new(
+                                egui::RichText::new("✗ This folder").size(12.0).color(egui::Color32::WHITE)
+                            )
+                            .fill(egui::Color32::from_rgb(229, 115, 115))
+                            .rounding(egui::Rounding::same(3.0))
+                            .min_size(egui::vec2(90.0, 25.0));
+                            
+                            if ui.add(deselect_here_btn).on_hover_text("Deselect files in this folder, not its subfolders").clicked() {
+                                self.select_in_folder(path, file_map, false);
+                            }
+                        }
+                        
+                        ui.add_space(8.0);
+                        let folder_label = if marked { "↩ Keep folder" } else { "🗂 Remove folder" };
+                        let folder_btn = egui::Button::new(
+                            egui::RichText::new(folder_label).size(12.0).color(egui::Color32::WHITE)
+                        )
+                        .fill(egui::Color32::from_rgb(198, 40, 40))
+                        .rounding(egui::Rounding::same(3.0))
+                        .min_size(egui::vec2(90.0, 25.0));
+                        
+                        let blocker = if marked { None } else { self.folder_removal_blocker(Path::new(path)) };
+                        let folder_response = ui.add_enabled(blocker.is_none(), folder_btn)
+                            .on_hover_text("Delete the folder itself with everything in it, \
+                                including files the scan didn't list")
+                            .on_disabled_hover_text(blocker.unwrap_or_default());
+                        if folder_response.clicked() {
+                            if marked {
+                                self.marked_folders.remove(Path::new(path));
+                            } else {
+                                self.marked_folders.insert(PathBuf::from(path));
+                            }
+                        }
+                    });
+                    
+                    // Render child directories
+                    if let Some(children) = tree.get(path) {
+                        for child in children {
+                            let expand_all = self.scan_results.len() <= self.auto_expand_below;
+                            self.render_tree_node(ui, child, tree, file_map, depth + 1, expand_all);
+                        }
+                    }
+                    
+                    // Render files in this directory
+                    if let Some(indices) = file_map.get(path) {
+                        ui.add_space(5.0);
+                        self.render_file_rows(ui, indices, indent + 20.0, false);
+                    }
+                });
+                
+                // Only record settled states, not the frames of the open/close animation
+                if response.fully_open() {
+                    if !self.expanded_folders.contains(path) {
+                        self.expanded_folders.insert(path.to_string());
+                    }
+                } else if response.fully_closed() {
+                    self.expanded_folders.remove(path);
+                }
+            });
+        }
+    }
+    
+    fn render_duplicates(&mut self, ui: &mut egui::Ui, groups: &[Vec<usize>]) {
+        let wasted_bytes: u64 = groups.iter()
+            .flat_map(|group| group.iter().skip(1))
+            .map(|&idx| self.scan_results[idx].size_bytes)
+            .sum();
+        
+        let header_text = egui::RichText::new(
+            format!("👯 Duplicates ({} groups, {} in extra copies)", groups.len(), human_size(wasted_bytes))
+        )
+        .color(egui::Color32::WHITE)
+        .size(13.0)
+        .strong();
+        
+        ui.add_space(3.0);
+        let header_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(123, 31, 162))
+            .inner_margin(egui::Margin::symmetric(10.0, 6.0))
+            .rounding(egui::Rounding::same(2.0));
+        
+        header_frame.show(ui, |ui| {
+            egui::CollapsingHeader::new(header_text)
+                .id_salt("duplicates")
+                .default_open(self.scan_results.len() <= self.auto_expand_below)
+                .show(ui, |ui| {
+                    for group in groups {
+                        let first = &self.scan_results[group[0]];
+                        ui.add_space(5.0);
+                        ui.label(egui::RichText::new(format!(
+                                "{}  —  {} copies of {}",
+                                first.file_name, group.len(), human_size(first.size_bytes)))
+                            .size(12.0)
+                            .strong());
+                        for &idx in group {
+                            self.render_file_row(ui, idx, 20.0, true);
+                        }
+                    }
+                });
+        });
+    }
+    
+    // Shown above every view, as a shortcut to the deletions that free the most space
+    fn render_top_results(&mut self, ui: &mut egui::Ui, largest: &[usize], oldest: &[usize]) {
+        let header_text = egui::RichText::new(format!("🏆 Top {} largest and oldest files", TOP_RESULTS))
+            .color(egui::Color32::WHITE)
+            .size(13.0)
+            .strong();
         
-        // Count files in this directory and all subdirectories
-        let (total_files, selected_files) = self.count_files_recursive(path, tree, file_map);
+        ui.add_space(3.0);
+        let header_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(0, 137, 123))
+            .inner_margin(egui::Margin::symmetric(10.0, 6.0))
+            .rounding(egui::Rounding::same(2.0));
         
-        if total_files > 0 {
-            ui.add_space(3.0);
-            
-            // Determine icon and color based on selection state
-            let icon = if depth == 0 { "📁" } else { "📂" };
-            let selection_status = if selected_files == total_files {
-                "✅" // All selected
-            } else if selected_files > 0 {
-                "⚠️" // Partially selected
-            } else {
-                "⬜" // None selected
-            };
-            
-            let header_text = egui::RichText::new(
-                format!("{} {} {} ({}/{})", 
-                    selection_status, icon, folder_name, selected_files, total_files)
-            )
+        header_frame.show(ui, |ui| {
+            egui::CollapsingHeader::new(header_text)
+                .id_salt("top_results")
+                .default_open(false)
+                .show(ui, |ui| {
+                    ui.add_space(5.0);
+                    ui.label(egui::RichText::new("📦 Largest").size(12.0).strong());
+                    for &idx in largest {
+                        self.render_file_row(ui, idx, 20.0, true);
+                    }
+                    
+                    ui.add_space(5.0);
+                    ui.label(egui::RichText::new("🕰️ Oldest").size(12.0).strong());
+                    for &idx in oldest {
+                        self.render_file_row(ui, idx, 20.0, true);
+                    }
+                });
+        });
+    }
+    
+    // One bar per extension, scaled to the biggest. Clicking an extension selects
+    // every file of that type.
+    fn render_type_sizes(&mut self, ui: &mut egui::Ui, by_type: &[(String, Vec<usize>)], type_sizes: &[(usize, u64)]) {
+        let header_text = egui::RichText::new("📊 Space by type")
             .color(egui::Color32::WHITE)
             .size(13.0)
             .strong();
-            
-            // Add background for the collapsing header
-            let header_frame = egui::Frame::none()
-                .fill(egui::Color32::from_rgb(63, 81, 181))
-                .inner_margin(egui::Margin::symmetric(10.0, 6.0))
-                .rounding(egui::Rounding::same(2.0));
-            
-            header_frame.show(ui, |ui| {
-                // Use a stable ID for the collapsing header to maintain state
-                egui::CollapsingHeader::new(header_text)
-                    .id_salt(path)
-                    .default_open(false)
-                    .show(ui, |ui| {
-                        ui.add_space(indent);
+        
+        ui.add_space(3.0);
+        let header_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(0, 137, 123))
+            .inner_margin(egui::Margin::symmetric(10.0, 6.0))
+            .rounding(egui::Rounding::same(2.0));
+        
+        header_frame.show(ui, |ui| {
+            egui::CollapsingHeader::new(header_text)
+                .id_salt("type_sizes")
+                .default_open(false)
+                .show(ui, |ui| {
+                    let biggest = type_sizes.first().map_or(0, |&(_, bytes)| bytes).max(1);
                     
-                    // Add select/deselect buttons with color
+                    egui::Grid::new("type_sizes_grid").num_columns(3).spacing([10.0, 4.0]).show(ui, |ui| {
+                        for &(group, bytes) in type_sizes {
+                            let (extension, indices) = &by_type[group];
+                            
+                            if ui.button(egui::RichText::new(extension).size(12.0))
+                                .on_hover_text(format!("Select all {} files", extension))
+                                .clicked()
+                            {
+                                for &idx in indices {
+                                    self.scan_results[idx].select(true);
+                                }
+                            }
+                            
+                            ui.add(egui::ProgressBar::new(bytes as f32 / biggest as f32)
+                                .desired_width(200.0)
+                                .fill(egui::Color32::from_rgb(0, 137, 123)));
+                            
+                            ui.label(egui::RichText::new(format!("{} · {} files", human_size(bytes), indices.len()))
+                                .size(12.0)
+                                .color(egui::Color32::from_rgb(100, 100, 100)));
+                            ui.end_row();
+                        }
+                    });
+                });
+        });
+    }
+    
+    fn render_incomplete_downloads(&mut self, ui: &mut egui::Ui, indices: &[usize]) {
+        let selected = indices.iter()
+            .filter(|&&idx| self.scan_results[idx].should_delete)
+            .count();
+        
+        let header_text = egui::RichText::new(
+            format!("⏬ Incomplete downloads ({}/{})", selected, indices.len())
+        )
+        .color(egui::Color32::WHITE)
+        .size(13.0)
+        .strong();
+        
+        ui.add_space(3.0);
+        let header_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(230, 81, 0))
+            .inner_margin(egui::Margin::symmetric(10.0, 6.0))
+            .rounding(egui::Rounding::same(2.0));
+        
+        header_frame.show(ui, |ui| {
+            egui::CollapsingHeader::new(header_text)
+                .id_salt("incomplete_downloads")
+                .default_open(self.scan_results.len() <= self.auto_expand_below)
+                .show(ui, |ui| {
+                    ui.add_space(5.0);
+                    self.render_file_rows(ui, indices, 20.0, false);
+                });
+        });
+    }
+    
+    fn render_type_group(&mut self, ui: &mut egui::Ui, extension: &str, indices: &[usize]) {
+        let selected = indices.iter()
+            .filter(|&&idx| self.scan_results[idx].should_delete)
+            .count();
+        let selection_status = if selected == indices.len() {
+            "✅"
+        } else if selected > 0 {
+            "⚠️"
+        } else {
+            "⬜"
+        };
+        
+        let header_text = egui::RichText::new(
+            format!("{} 🏷️ {} ({}/{})", selection_status, extension, selected, indices.len())
+        )
+        .color(egui::Color32::WHITE)
+        .size(13.0)
+        .strong();
+        
+        ui.add_space(3.0);
+        let header_frame = egui::Frame::none()
+            .fill(egui::Color32::from_rgb(63, 81, 181))
+            .inner_margin(egui::Margin::symmetric(10.0, 6.0))
+            .rounding(egui::Rounding::same(2.0));
+        
+        header_frame.show(ui, |ui| {
+            egui::CollapsingHeader::new(header_text)
+                .id_salt(("type_group", extension))
+                .default_open(self.scan_results.len() <= self.auto_expand_below)
+                .show(ui, |ui| {
                     ui.horizontal(|ui| {
-                        ui.add_space(indent);
-                        
                         let select_btn = egui::Button::new(
                             egui::RichText::new("✓ Select All").size(12.0).color(egui::Color32::WHITE)
                         )
-                        .fill(egui::Color32::from_rgb(244, 67, 54))
+                        .fill(egui::Color32::from_rgb(76, 175, 80))
                         .rounding(egui::Rounding::same(3.0))
                         .min_size(egui::vec2(90.0, 25.0));
                         
                         if ui.add(select_btn).clicked() {
-                            self.select_all_recursive(path, tree, file_map, true);
+                            for &idx in indices {
+                                self.scan_results[idx].select(true);
+                            }
                         }
                         
                         let deselect_btn = egui::Button::new(
                             egui::RichText::new("✗ Deselect All").size(12.0).color(egui::Color32::WHITE)
                         )
-                        .fill(egui::Color32::from_rgb(76, 175, 80))
+                        .fill(egui::Color32::from_rgb(158, 158, 158))
                         .rounding(egui::Rounding::same(3.0))
                         .min_size(egui::vec2(90.0, 25.0));
                         
                         if ui.add(deselect_btn).clicked() {
-                            self.select_all_recursive(path, tree, file_map, false);
+                            for &idx in indices {
+                                self.scan_results[idx].should_delete = false;
+                            }
                         }
                     });
                     
-                    // Render child directories
-                    if let Some(children) = tree.get(path) {
-                        for child in children {
-                            self.render_tree_node(ui, child, tree, file_map, depth + 1);
+                    ui.add_space(5.0);
+                    self.render_file_rows(ui, indices, 20.0, true);
+                });
+        });
+    }
+    
+    // `full_path` shows where the file is, for views that don't group by folder
+    // Lays out only the rows inside the visible part of the scroll area, with empty
+    // space standing in for the rest, so folders with thousands of files stay fast.
+    // Every row is one line, so the height of the last one drawn fits them all.
+    fn render_file_rows(&mut self, ui: &mut egui::Ui, indices: &[usize], indent: f32, full_path: bool) {
+        let stride = self.file_row_height + ui.spacing().item_spacing.y;
+        let top = ui.cursor().top();
+        let visible = ui.clip_rect();
+        
+        let first = (((visible.top() - top) / stride).floor().max(0.0) as usize).min(indices.len());
+        let last = (((visible.bottom() - top) / stride).ceil().max(0.0) as usize + 1).clamp(first, indices.len());
+        
+        ui.add_space(first as f32 * stride);
+        let mut clicked = None;
+        for &idx in &indices[first..last] {
+            let row = ui.scope(|ui| self.render_file_row(ui, idx, indent, full_path));
+            self.file_row_height = row.response.rect.height();
+            if row.inner {
+                clicked = Some(idx);
+            }
+        }
+        ui.add_space((indices.len() - last) as f32 * stride);
+        
+        if let Some(idx) = clicked {
+            self.checkbox_clicked(ui, indices, idx);
+        }
+    }
+    
+    // Returns whether the row's checkbox was clicked
+    fn render_file_row(&mut self, ui: &mut egui::Ui, idx: usize, indent: f32, full_path: bool) -> bool {
+        let age = format_age(&self.scan_results[idx], self.date_display, &self.date_format);
+        let matched = self.result_tree.as_ref().and_then(|tree| tree.highlights.get(&idx).cloned());
+        let thumbnails = &mut self.thumbnails;
+        let result = &mut self.scan_results[idx];
+        let mut toggle_keep = false;
+        let mut checkbox_clicked = false;
+        
+        // Color code the row based on selection
+        let bg_color = if result.kept {
+            egui::Color32::from_rgb(238, 238, 238) // Grey
+        } else if result.should_delete {
+            egui::Color32::from_rgb(255, 235, 235) // Light red
+        } else {
+            egui::Color32::from_rgb(235, 255, 235) // Light green
+        };
+        
+        let frame = egui::Frame::none()
+            .fill(bg_color)
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(200, 200, 200)))
+            .inner_margin(egui::Margin::same(6.0))
+            .rounding(egui::Rounding::same(3.0));
+        
+        let row = frame.show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.add_space(indent);
+                checkbox_clicked = ui.add_enabled(!result.kept, egui::Checkbox::without_text(&mut result.should_delete))
+                    .on_disabled_hover_text("Kept files are never deleted")
+                    .clicked();
+                
+                let file_icon = if result.kept {
+                    "🔒"
+                } else if result.should_delete {
+                    "🗑️"
+                } else {
+                    "📄"
+                };
+                ui.label(file_icon);
+                
+                let path = result.file_path.to_string_lossy();
+                let name = if full_path { path.clone() } else { result.file_name.as_str().into() };
+                let name_label = match &matched {
+                    Some(positions) => ui.label(highlighted_text(&name, path.chars().count(), positions, 13.0)),
+                    None => ui.label(egui::RichText::new(name)
+                        .color(egui::Color32::BLACK)
+                        .size(13.0)),
+                };
+                name_label.on_hover_ui(|ui| file_hover(ui, result, thumbnails));
+                
+                if let Some(badge) = hollow_badge(result) {
+                    ui.label(badge);
+                }
+                if let Some(error) = &result.delete_error {
+                    ui.label(egui::RichText::new("⚠ not removed")
+                            .size(11.0)
+                            .color(egui::Color32::from_rgb(244, 67, 54)))
+                        .on_hover_text(error);
+                }
+                
+                if let Some(kind) = result.volume {
+                    ui.label(kind.icon()).on_hover_text(format!("On a {}", kind.label()));
+                }
+                
+                ui.label(egui::RichText::new(format!("({})", age))
+                    .color(egui::Color32::from_rgb(100, 100, 100))
+                    .size(12.0))
+                    .on_hover_text(format!("{} days", result.days_since_access));
+                
+                ui.label(egui::RichText::new(human_size(result.size_bytes))
+                    .color(egui::Color32::from_rgb(100, 100, 100))
+                    .size(12.0));
+                
+                if let Some(count) = result.use_count {
+                    ui.label(egui::RichText::new(format!("×{} uses", count))
+                        .color(egui::Color32::from_rgb(100, 100, 100))
+                        .size(12.0));
+                }
+                
+                let keep_label = if result.kept { "🔓 Unkeep" } else { "🔒 Keep" };
+                toggle_keep = ui.small_button(keep_label)
+                    .on_hover_text("Kept files stay listed but are never selected for deletion")
+                    .clicked();
+            });
+        });
+        
+        if toggle_keep {
+            let result = &mut self.scan_results[idx];
+            result.kept = !result.kept;
+            result.should_delete = false;
+            let path = result.file_path.to_string_lossy().to_string();
+            if result.kept {
+                self.never_delete.insert(path);
+            } else {
+                self.never_delete.remove(&path);
+            }
+        }
+        
+        // Right-click a row to look at the file before deciding on it
+        let file_path = &self.scan_results[idx].file_path;
+        let mut reveal_error = None;
+        row.response.interact(egui::Sense::click()).context_menu(|ui| {
+            if ui.button("📂 Open containing folder").clicked() {
+                reveal_error = reveal_in_file_manager(file_path).err();
+                ui.close_menu();
+            }
+            if ui.button("📋 Copy path").clicked() {
+                ui.ctx().copy_text(file_path.to_string_lossy().to_string());
+                ui.close_menu();
+            }
+        });
+        
+        if let Some(e) = reveal_error {
+            self.status_message = format!("❌ Could not open the folder: {}", e);
+        }
+        checkbox_clicked
+    }
+    
+    // Shift-clicking a checkbox sets every row from the last one clicked to this one
+    // the same way. `order` is the list the row was drawn from, so a range stays
+    // within one folder in the tree.
+    fn checkbox_clicked(&mut self, ui: &egui::Ui, order: &[usize], idx: usize) {
+        let position = |path: &PathBuf| order.iter().position(|&i| self.scan_results[i].file_path == *path);
+        if ui.input(|i| i.modifiers.shift)
+            && let Some(anchor) = self.last_clicked.as_ref().and_then(position)
+            && let Some(clicked) = order.iter().position(|&i| i == idx)
+        {
+            let selected = self.scan_results[idx].should_delete;
+            for &i in &order[anchor.min(clicked)..=anchor.max(clicked)] {
+                self.scan_results[i].select(selected);
+            }
+        }
+        self.last_clicked = Some(self.scan_results[idx].file_path.clone());
+    }
+    
+    // The Flat view as a table. Clicking a column header sorts by it, clicking it again
+    // flips the direction. The outer results area does the scrolling, and only the rows
+    // in view get laid out.
+    fn render_flat_table(&mut self, ui: &mut egui::Ui, indices: &[usize]) {
+        let columns = [
+            (SortKey::Name, "Name"),
+            (SortKey::Size, "Size"),
+            (SortKey::DaysSinceAccess, "Age"),
+            (SortKey::Path, "Path"),
+        ];
+        let row_height = ui.spacing().interact_size.y + 4.0;
+        let mut sort_clicked = None;
+        let mut reveal_error = None;
+        let mut clicked = None;
+        
+        egui_extras::TableBuilder::new(ui)
+            .id_salt("flat_table")
+            .striped(true)
+            .vscroll(false)
+            .sense(egui::Sense::click())
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::exact(24.0))
+            .column(egui_extras::Column::initial(220.0).at_least(80.0).resizable(true).clip(true))
+            .column(egui_extras::Column::initial(80.0).at_least(60.0).resizable(true))
+            .column(egui_extras::Column::initial(110.0).at_least(60.0).resizable(true))
+            .column(egui_extras::Column::remainder().at_least(80.0).clip(true))
+            .header(24.0, |mut header| {
+                header.col(|_| {});
+                for (key, title) in columns {
+                    header.col(|ui| {
+                        let sorted = self.sort_key == key;
+                        let arrow = match (sorted, self.sort_descending) {
+                            (false, _) => "",
+                            (true, false) => " ⬆",
+                            (true, true) => " ⬇",
+                        };
+                        let text = egui::RichText::new(format!("{}{}", title, arrow))
+                            .size(12.0)
+                            .strong()
+                            .color(egui::Color32::BLACK);
+                        if ui.selectable_label(sorted, text).clicked() {
+                            sort_clicked = Some(key);
                         }
-                    }
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, indices.len(), |mut row| {
+                    let idx = indices[row.index()];
+                    let age = format_age(&self.scan_results[idx], self.date_display, &self.date_format);
+                    let matched = self.result_tree.as_ref().and_then(|tree| tree.highlights.get(&idx).cloned());
+                    let result = &mut self.scan_results[idx];
+                    let muted = egui::Color32::from_rgb(100, 100, 100);
                     
-                    // Render files in this directory
-                    if let Some(indices) = file_map.get(path) {
-                        ui.add_space(5.0);
-                        for &idx in indices {
-                            let result = &mut self.scan_results[idx];
-                            
-                            // Color code the row based on selection
-                            let bg_color = if result.should_delete {
-                                egui::Color32::from_rgb(255, 235, 235) // Light red
-                            } else {
-                                egui::Color32::from_rgb(235, 255, 235) // Light green
-                            };
-                            
-                            let frame = egui::Frame::none()
-                                .fill(bg_color)
-                                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(200, 200, 200)))
-                                .inner_margin(egui::Margin::same(6.0))
-                                .rounding(egui::Rounding::same(3.0));
-                            
-                            frame.show(ui, |ui| {
-                                ui.horizontal(|ui| {
-                                    ui.add_space(indent + 20.0);
-                                    ui.checkbox(&mut result.should_delete, "");
-                                    
-                                    let file_icon = if result.should_delete { "🗑️" } else { "📄" };
-                                    ui.label(file_icon);
-                                    
-                                    ui.label(egui::RichText::new(&result.file_name)
-                                        .color(egui::Color32::BLACK)
-                                        .size(13.0));
-                                    
-                                    ui.label(egui::RichText::new(format!("({} days)", result.days_since_access))
-                                        .color(egui::Color32::from_rgb(100, 100, 100))
-                                        .size(12.0));
-                                });
-                            });
+                    row.col(|ui| {
+                        if ui.add_enabled(!result.kept, egui::Checkbox::without_text(&mut result.should_delete))
+                            .on_disabled_hover_text("Kept files are never deleted")
+                            .clicked()
+                        {
+                            clicked = Some(idx);
                         }
-                    }
+                    });
+                    row.col(|ui| {
+                        if result.kept {
+                            ui.label("🔒");
+                        }
+                        let name_label = match &matched {
+                            Some(positions) => {
+                                let path_chars = result.file_path.to_string_lossy().chars().count();
+                                ui.label(highlighted_text(&result.file_name, path_chars, positions, 13.0))
+                            }
+                            None => ui.label(egui::RichText::new(&result.file_name)
+                                .color(egui::Color32::BLACK)
+                                .size(13.0)),
+                        };
+                        name_label.on_hover_ui(|ui| file_hover(ui, result, &mut self.thumbnails));
+                        if let Some(badge) = hollow_badge(result) {
+                            ui.label(badge);
+                        }
+                        if let Some(error) = &result.delete_error {
+                            ui.label(egui::RichText::new("⚠ not removed")
+                                    .size(11.0)
+                                    .color(egui::Color32::from_rgb(244, 67, 54)))
+                                .on_hover_text(error);
+                        }
+                        if let Some(kind) = result.volume {
+                            ui.label(kind.icon()).on_hover_text(format!("On a {}", kind.label()));
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(egui::RichText::new(human_size(result.size_bytes)).color(muted).size(12.0));
+                    });
+                    row.col(|ui| {
+                        ui.label(egui::RichText::new(age).color(muted).size(12.0))
+                            .on_hover_text(format!("{} days", result.days_since_access));
+                    });
+                    row.col(|ui| {
+                        let folder = result.file_path.parent().unwrap_or(&result.file_path);
+                        ui.label(egui::RichText::new(folder.to_string_lossy()).color(muted).size(12.0))
+                            .on_hover_text(result.file_path.to_string_lossy());
+                    });
+                    
+                    let file_path = &result.file_path;
+                    row.response().context_menu(|ui| {
+                        if ui.button("📂 Open containing folder").clicked() {
+                            reveal_error = reveal_in_file_manager(file_path).err();
+                            ui.close_menu();
+                        }
+                        if ui.button("📋 Copy path").clicked() {
+                            ui.ctx().copy_text(file_path.to_string_lossy().to_string());
+                            ui.close_menu();
+                        }
+                    });
                 });
             });
+        
+        // Changing the sort rebuilds the cached tree on the next frame
+        if let Some(key) = sort_clicked {
+            if self.sort_key == key {
+                self.sort_descending = !self.sort_descending;
+            } else {
+                self.sort_key = key;
+                self.sort_descending = false;
+            }
+        }
+        if let Some(e) = reveal_error {
+            self.status_message = format!("❌ Could not open the folder: {}", e);
+        }
+        if let Some(idx) = clicked {
+            self.checkbox_clicked(ui, indices, idx);
+        }
+    }
+    
+    fn selected_bytes(&self) -> u64 {
+        self.scope_bytes(&DeleteScope::Selected)
+    }
+    
+    fn scope_bytes(&self, scope: &DeleteScope) -> u64 {
+        self.scan_results.iter()
+            .filter(|r| scope.includes(r))
+            .map(|r| r.size_bytes)
+            .sum()
+    }
+    
+    fn count_files_recursive(
+        &self,
+        path: &str,
+        tree: &HashMap<String, Vec<String>>,
+        file_map: &HashMap<String, Vec<usize>>,
+    ) -> (usize, usize) {
+        let mut total = 0;
+        let mut selected = 0;
+        
+        // Count files in this directory
+        if let Some(indices) = file_map.get(path) {
+            total += indices.len();
+            selected += indices.iter()
+                .filter(|&&idx| self.scan_results[idx].should_delete)
+                .count();
+        }
+        
+        // Count files in subdirectories
+        if let Some(children) = tree.get(path) {
+            for child in children {
+                let (child_total, child_selected) = self.count_files_recursive(child, tree, file_map);
+                total += child_total;
+                selected += child_selected;
+            }
+        }
+        
+        (total, selected)
+    }
+    
+    // Why a folder can't be removed whole, if it can't. Only folders inside a scan
+    // target qualify, and nothing protected or on the never-delete list may go with them.
+    fn folder_removal_blocker(&self, folder: &Path) -> Option<&'static str> {
+        if !self.scan_roots.iter().any(|root| folder.starts_with(root) && folder != root.as_path()) {
+            return Some("Only folders inside a scanned directory can be removed whole");
+        }
+        if scanner::is_protected(folder, &scanner::protected_prefixes(&self.protected_paths)) {
+            return Some("This folder is protected");
+        }
+        let keeps_something = self.scan_results.iter().any(|r| r.kept && r.file_path.starts_with(folder))
+            || self.never_delete.iter().any(|kept| Path::new(kept).starts_with(folder));
+        if keeps_something {
+            return Some("This folder holds files you chose to keep");
+        }
+        None
+    }
+    
+    fn select_all_recursive(
+        &mut self,
+        path: &str,
+        tree: &HashMap<String, Vec<String>>,
+        file_map: &HashMap<String, Vec<usize>>,
+        select: bool,
+    ) {
+        self.select_in_folder(path, file_map, select);
+        
+        // Recursively select/deselect in subdirectories
+        if let Some(children) = tree.get(path) {
+            for child in children {
+                self.select_all_recursive(child, tree, file_map, select);
+            }
+        }
+    }
+    
+    // Select/deselect files directly in this directory, leaving subdirectories alone
+    fn select_in_folder(&mut self, path: &str, file_map: &HashMap<String, Vec<usize>>, select: bool) {
+        if let Some(indices) = file_map.get(path) {
+            for &idx in indices {
+                self.scan_results[idx].select(select);
+            }
+        }
+    }
+    
+    fn add_custom_directory(&mut self) {
+        let directory = self.new_directory.trim().to_string();
+        
+        if directory.is_empty() {
+            self.directory_error = Some("Enter a directory path first.".to_string());
+            return;
+        }
+        match self.push_custom_directory(directory) {
+            Ok(()) => {
+                self.new_directory.clear();
+                self.directory_error = None;
+            }
+            Err(e) => self.directory_error = Some(e),
+        }
+    }
+    
+    fn push_custom_directory(&mut self, directory: String) -> Result<(), String> {
+        if !std::path::Path::new(&directory).exists() {
+            Err(format!("{} doesn't exist.", directory))
+        } else if !std::path::Path::new(&directory).is_dir() {
+            Err(format!("{} is not a directory.", directory))
+        } else if self.custom_directories.contains(&directory) {
+            Err(format!("{} is already in the list.", directory))
+        } else {
+            self.custom_directories.push(directory);
+            Ok(())
+        }
+    }
+    
+    fn open_delete_confirm(&mut self, scope: DeleteScope) {
+        self.confirm_delete_scope = scope;
+        self.confirm_estimate_for = None;
+        self.refresh_delete_estimate();
+        self.confirm_delete_acknowledged = false;
+        self.confirm_delete_open = true;
+    }
+    
+    // The files in the pending delete's scope, their associated files and any marked
+    // folders, without counting anything twice that sits inside a marked folder. Finding
+    // associated files and measuring folders reads the disk, so it's skipped while the
+    // selection is unchanged.
+    fn refresh_delete_estimate(&mut self) {
+        let scope = self.confirm_delete_scope.clone();
+        let marked_folders = if scope.takes_marked_folders() { self.marked_folders.clone() } else { BTreeSet::new() };
+        let selection = (
+            scope.clone(),
+            self.scan_results.iter().filter(|r| scope.includes(r)).count(),
+            self.scope_bytes(&scope),
+            marked_folders.clone(),
+        );
+        if self.confirm_estimate_for.as_ref() == Some(&selection) {
+            return;
+        }
+        
+        let in_marked_folder = |path: &Path| marked_folders.iter().any(|folder| path.starts_with(folder) && path != folder);
+        self.confirm_associated = self.scan_results.iter()
+            .filter(|result| scope.includes(result))
+            .flat_map(|result| self.find_associated_files(&result.file_path))
+            .collect();
+        let file_bytes: u64 = self.scan_results.iter()
+            .filter(|r| scope.includes(r) && !in_marked_folder(&r.file_path))
+            .map(|r| r.size_bytes)
+            .sum();
+        let associated_bytes: u64 = self.confirm_associated.iter()
+            .filter(|path| !in_marked_folder(path))
+            .map(|path| fs::metadata(path).map(|m| m.len()).unwrap_or(0))
+            .sum();
+        let folder_bytes: u64 = marked_folders.iter()
+            .filter(|folder| !in_marked_folder(folder))
+            .map(|folder| scanner::path_size(folder))
+            .sum();
+        
+        self.confirm_free_bytes = file_bytes + associated_bytes + folder_bytes;
+        self.confirm_estimate_for = Some(selection);
+    }
+    
+    // Nothing fires while a text field has the keyboard, so typing in the path or
+    // search box is left alone, or while a dialog is waiting for an answer
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        let dialog_open = self.confirm_delete_open
+            || self.confirm_empty_trash_open
+            || self.quick_clean.is_some()
+            || self.external_scan_warning.is_some()
+            || self.pending_restore.is_some();
+        if ctx.wants_keyboard_input() || dialog_open {
+            return;
+        }
+        
+        let (select_all, deselect_all, delete, cancel) = ctx.input_mut(|i| (
+            i.consume_key(egui::Modifiers::COMMAND, egui::Key::A),
+            i.consume_key(egui::Modifiers::COMMAND, egui::Key::D),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Delete),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+        ));
+        // Enter on a focused button or checkbox already means that widget
+        let scan = ctx.memory(|m| m.focused().is_none())
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Enter));
+        
+        if select_all {
+            for result in &mut self.scan_results {
+                result.select(true);
+            }
+        }
+        if deselect_all {
+            for result in &mut self.scan_results {
+                result.should_delete = false;
+            }
+        }
+        let busy = self.is_busy();
+        let anything_selected = self.scan_results.iter().any(|r| r.should_delete) || !self.marked_folders.is_empty();
+        if delete && !self.is_scanning && !busy && anything_selected {
+            self.open_delete_confirm(DeleteScope::Selected);
+        }
+        if cancel && self.is_scanning {
+            self.scan_progress.cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+        }
+        if let Some(deletion) = &self.deletion
+            && cancel
+        {
+            deletion.cancel();
+        }
+        if scan && !self.is_scanning && !busy {
+            self.scan_files();
         }
     }
     
-    fn count_files_recursive(
-        &self,
-        path: &str,
-        tree: &HashMap<String, Vec<String>>,
-        file_map: &HashMap<String, Vec<usize>>,
-    ) -> (usize, usize) {
-        let mut total = 0;
-        let mut selected = 0;
+    // Folders dropped anywhere on the window become custom directories. While
+    // something is dragged over it, the window is tinted to show it'll be taken.
+    fn handle_dropped_folders(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+            let painter = ctx.layer_painter(egui::LayerId::new(
+                egui::Order::Foreground, egui::Id::new("folder_drop_target")));
+            let screen = ctx.screen_rect();
+            painter.rect(
+                screen.shrink(4.0),
+                4.0,
+                egui::Color32::from_rgba_unmultiplied(63, 81, 181, 60),
+                egui::Stroke::new(3.0, egui::Color32::from_rgb(63, 81, 181)),
+            );
+            painter.text(
+                screen.center(),
+                egui::Align2::CENTER_CENTER,
+                "📁 Drop folders to scan them",
+                egui::FontId::proportional(20.0),
+                egui::Color32::from_rgb(63, 81, 181),
+            );
+        }
         
-        // Count files in this directory
-        if let Some(indices) = file_map.get(path) {
-            total += indices.len();
-            selected += indices.iter()
-                .filter(|&&idx| self.scan_results[idx].should_delete)
-                .count();
+        let dropped: Vec<PathBuf> = ctx.input(|i| {
+            i.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect()
+        });
+        if dropped.is_empty() {
+            return;
         }
         
-        // Count files in subdirectories
-        if let Some(children) = tree.get(path) {
-            for child in children {
-                let (child_total, child_selected) = self.count_files_recursive(child, tree, file_map);
-                total += child_total;
-                selected += child_selected;
+        let mut added = 0;
+        let mut errors = Vec::new();
+        for path in dropped {
+            match self.push_custom_directory(path.to_string_lossy().to_string()) {
+                Ok(()) => added += 1,
+                Err(e) => errors.push(e),
             }
         }
+        // The custom directory list may be hidden with the settings panel, so say so here too
+        if added > 0 {
+            self.status_message = format!("📁 Added {} folders to the custom directories.", added);
+        }
+        self.directory_error = (!errors.is_empty()).then(|| errors.join(" "));
+    }
+    
+    // The name shared by a file and its associated files, if the file has the trigger extension
+    fn find_associated_files(&self, trigger_path: &Path) -> Vec<PathBuf> {
+        deletion::associated_files(trigger_path, &self.associated_trigger_extension, &self.associated_extensions)
+    }
         
-        (total, selected)
+    fn scan_files(&mut self) {
+        // Crawling a network share or USB drive can take minutes, so ask once per folder
+        let volumes = ExternalVolumes::detect();
+        let unconfirmed: Vec<(String, VolumeKind)> = self.settings().search_directories()
+            .into_iter()
+            .filter(|directory| !self.confirmed_external_directories.contains(directory))
+            .filter_map(|directory| {
+                let kind = volumes.kind_of(Path::new(&directory))?;
+                Some((directory, kind))
+            })
+            .collect();
+        if !unconfirmed.is_empty() {
+            self.external_scan_warning = Some(unconfirmed);
+            return;
+        }
+        
+        self.is_scanning = true;
+        // A rescan picks the selection back up for files that are found again
+        self.previous_selection = self.scan_results.iter()
+            .map(|result| (result.file_path.clone(), result.should_delete))
+            .collect();
+        self.scan_results.clear();
+        self.result_tree = None;
+        self.skipped_large_dirs.clear();
+        self.scan_errors.clear();
+        self.filtered_paths.clear();
+        self.last_stats = None;
+        self.dry_run_log.clear();
+        self.status_message = "Scanning...".to_string();
+        
+        let directories = self.settings().search_directories();
+        
+        // A previous sandbox is stale as soon as a new scan starts
+        self.discard_sandbox();
+        let copies = if self.sandbox_mode {
+            match sandbox::create_sandbox() {
+                Ok(root) => {
+                    let copies = sandbox::sandbox_targets(&root, &directories);
+                    self.sandbox_root = Some(root);
+                    copies
+                }
+                Err(e) => {
+                    self.status_message = format!("❌ Could not create sandbox copy: {}", e);
+                    self.is_scanning = false;
+                    return;
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        
+        // In sandbox mode the copies are scanned instead of the originals
+        let directories: Vec<PathBuf> = if self.sandbox_mode {
+            copies.iter().map(|(_, copy)| copy.clone()).collect()
+        } else {
+            directories.into_iter().map(PathBuf::from).collect()
+        };
+        
+        self.scan_roots = directories.clone();
+        self.start_scan(directories, copies, self.scan_options(), ScanKind::Full);
     }
     
-    fn select_all_recursive(
+    // The saved settings' options, with what only the window keeps on top
+    fn scan_options(&self) -> ScanOptions {
+        ScanOptions {
+            frequent_use_count: self.frequent_use_count,
+            large_dirs_allowed: self.large_dirs_allowed.clone(),
+            incomplete_downloads_enabled: self.incomplete_downloads_enabled,
+            incomplete_download_days: self.incomplete_download_days,
+            list_filtered: self.list_filtered,
+            ..self.settings().scan_options()
+        }
+    }
+    
+    fn start_scan(
         &mut self,
-        path: &str,
-        tree: &HashMap<String, Vec<String>>,
-        file_map: &HashMap<String, Vec<usize>>,
-        select: bool,
+        directories: Vec<PathBuf>,
+        copies: Vec<(PathBuf, PathBuf)>,
+        options: ScanOptions,
+        kind: ScanKind,
     ) {
-        // Select/deselect files in this directory
-        if let Some(indices) = file_map.get(path) {
-            for &idx in indices {
-                self.scan_results[idx].should_delete = select;
+        self.is_scanning = true;
+        self.scan_kind = kind;
+        self.marked_folders.clear();
+        self.selected_tab = Tab::Results;
+        self.scan_progress = Arc::default();
+        self.scan_receiver = Some(scanner::spawn_scan(
+            directories, copies, options, Arc::clone(&self.scan_progress)));
+    }
+    
+    // Drains whatever the worker has sent since the last frame
+    fn poll_scan(&mut self) {
+        let Some(receiver) = &self.scan_receiver else {
+            return;
+        };
+        
+        let mut finished = None;
+        let mut found_any = false;
+        loop {
+            match receiver.try_recv() {
+                Ok(ScanEvent::Found(mut result)) => {
+                    found_any = true;
+                    if let Some(&selected) = self.previous_selection.get(&result.file_path) {
+                        result.select(selected);
+                    }
+                    self.scan_results.push(result);
+                }
+                Ok(ScanEvent::LargeDirectory(path, entry_count)) => {
+                    self.skipped_large_dirs.push((path, entry_count));
+                }
+                Ok(ScanEvent::Unreadable(path, error)) => {
+                    self.scan_errors.push((path, error));
+                }
+                Ok(ScanEvent::Filtered(path, category)) => {
+                    self.filtered_paths.push((path, category));
+                }
+                Ok(ScanEvent::Failed(error)) => {
+                    self.status_message = format!("❌ {}", error);
+                    self.discard_sandbox();
+                    finished = Some(None);
+                    break;
+                }
+                Ok(ScanEvent::Finished(stats)) => {
+                    finished = Some(Some(stats));
+                    break;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    finished = Some(None);
+                    break;
+                }
             }
         }
         
-        // Recursively select/deselect in subdirectories
-        if let Some(children) = tree.get(path) {
-            for child in children {
-                self.select_all_recursive(child, tree, file_map, select);
-            }
+        if found_any && self.tree_stale_since.is_none() {
+            self.tree_stale_since = Some(std::time::Instant::now());
         }
-    }
-    
-    fn should_exclude_file(&self, file_name: &str) -> bool {
-        if !self.smart_filter_enabled {
-            return false;
+        if self.tree_stale_since.is_some_and(|since| finished.is_some() || since.elapsed() >= TREE_REFRESH_INTERVAL) {
+            self.result_tree = None;
+            self.tree_stale_since = None;
         }
         
-        let file_lower = file_name.to_lowercase();
+        let Some(outcome) = finished else {
+            return;
+        };
         
-        // Binary and supporting files (excluding .exe which we want to check)
-        let binary_extensions = [
-            ".dll", ".so", ".dylib", ".bin", ".o", ".a", 
-            ".lib", ".sys", ".drv", ".class", ".pyc", ".pyo",
-        ];
+        self.scan_receiver = None;
+        self.is_scanning = false;
         
-        // System and cache files
-        let system_patterns = [
-            ".cache", ".tmp", ".temp", ".log", ".bak", ".swp", ".swo",
-            ".lock", ".pid", ".dat", ".db", ".sqlite", ".idx",
-        ];
+        // Back to where the list was before a rescan, now that it's complete again
+        if !self.previous_selection.is_empty() {
+            self.previous_selection.clear();
+            self.restore_scroll = Some(self.results_scroll_offset);
+        }
         
-        // Build and dependency directories content
-        let build_patterns = [
-            "node_modules", "target", "build", "dist", ".git", ".svn",
-        ];
+        // A failed scan has already reported why
+        let Some(stats) = outcome else {
+            return;
+        };
+        self.last_stats = Some(stats);
+        self.filtered_paths.sort_by(|a, b| a.0.cmp(&b.0));
+        
+        let cancelled = self.scan_progress.is_cancelled();
+        
+        // Results arrive in whatever order the worker threads get to them
+        self.scan_results.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        self.skipped_large_dirs.sort();
+        self.scan_errors.sort();
+        self.result_tree = None;
         
-        // Check extensions
-        for ext in &binary_extensions {
-            if file_lower.ends_with(ext) {
-                return true;
+        match std::mem::replace(&mut self.scan_kind, ScanKind::Full) {
+            // Whatever was found before cancelling stays for review, but a half-done
+            // Quick Clean doesn't go on to offer deleting it
+            _ if cancelled => {
+                self.status_message = format!(
+                    "Scan cancelled. Kept the {} files found so far.", self.scan_results.len());
+            }
+            ScanKind::Full => {
+                self.status_message = match &self.sandbox_root {
+                    Some(root) => format!(
+                        "Sandbox scan complete. Found {} files in copy at {}.",
+                        self.scan_results.len(), root.display()),
+                    None => format!("Scan complete. Found {} files.", self.scan_results.len()),
+                };
+            }
+            ScanKind::QuickClean => {
+                let found_bytes = self.scan_results.iter().map(|r| r.size_bytes).sum();
+                
+                self.status_message = format!(
+                    "Quick Clean found {} files in Downloads.", self.scan_results.len());
+                self.quick_clean = Some(QuickClean::Review { before_bytes: stats.bytes_scanned, found_bytes });
+            }
+            ScanKind::Directory { path, results_before } => {
+                self.status_message = format!(
+                    "Scanned {}: found {} more files.", path.display(), self.scan_results.len() - results_before);
             }
         }
         
-        // Check system patterns
-        for pattern in &system_patterns {
-            if file_lower.contains(pattern) {
-                return true;
-            }
+        self.status_message += &Self::stats_summary(&stats);
+        
+        if !self.scan_errors.is_empty() {
+            self.status_message += &format!(
+                " {} paths could not be read, so the scan may be incomplete.", self.scan_errors.len());
         }
         
-        // Check if file is in a build/dependency directory
-        for pattern in &build_patterns {
-            if file_lower.contains(pattern) {
-                return true;
-            }
+        if stats.broken_symlinks > 0 {
+            self.status_message += &format!(" Found {} broken symlinks.", stats.broken_symlinks);
+        }
+        
+        if stats.timestamp_fallbacks > 0 {
+            // Combining always needs the access time, whatever the basis is set to
+            let missing = match self.time_combiner {
+                TimeCombiner::Single => self.time_basis.verb(),
+                _ => TimeBasis::Accessed.verb(),
+            };
+            self.status_message += &format!(
+                " {} files had no {} time, so their modified (or created) time was used.",
+                stats.timestamp_fallbacks, missing);
         }
         
-        false
+        if self.trash_report_enabled {
+            self.refresh_trash();
+        }
+        self.refresh_drive_space();
     }
     
-    fn get_exe_base_name(path: &str) -> Option<String> {
-        if path.to_lowercase().ends_with(".exe") {
-            let file_name = std::path::Path::new(path)
-                .file_stem()?
-                .to_str()?;
-            Some(file_name.to_string())
-        } else {
-            None
+    // e.g. " Took 4.2s over 310 folders. Left out 12 by exclude patterns, 95 as recently used."
+    // Reasons that didn't leave anything out aren't mentioned. What the filters took out
+    // gets its own line, see render_filter_tally.
+    fn stats_summary(stats: &ScanStats) -> String {
+        let mut summary = format!(
+            " Took {} over {} folders.", human_duration(stats.elapsed), stats.directories);
+        
+        let skipped: Vec<String> = [
+            (stats.excluded, "by exclude patterns"),
+            (stats.too_recent, "as recently used"),
+        ]
+        .into_iter()
+        .filter(|&(count, _)| count > 0)
+        .map(|(count, reason)| format!("{} {}", count, reason))
+        .collect();
+        if !skipped.is_empty() {
+            summary += &format!(" Left out {}.", skipped.join(", "));
         }
+        summary
     }
     
-    fn find_associated_files(&self, exe_path: &str) -> Vec<String> {
-        let mut associated_files = Vec::new();
-        
-        let Some(base_name) = Self::get_exe_base_name(exe_path) else {
-            return associated_files;
+    // e.g. "Filtered out: 100 (binary 12, system 5, build 83)". Files that weren't on
+    // the junk list or didn't have an included extension count as other.
+    fn render_filter_tally(&self, ui: &mut egui::Ui) {
+        let Some(stats) = &self.last_stats else {
+            return;
         };
+        if stats.filtered == 0 {
+            return;
+        }
         
-        let exe_dir = std::path::Path::new(exe_path).parent();
-        let Some(dir) = exe_dir else {
-            return associated_files;
-        };
+        let other = stats.filtered - stats.filtered_binary - stats.filtered_system - stats.filtered_build;
+        let parts: Vec<String> = [
+            (stats.filtered_binary, "binary"),
+            (stats.filtered_system, "system"),
+            (stats.filtered_build, "build"),
+            (other, "other"),
+        ]
+        .into_iter()
+        .filter(|&(count, _)| count > 0)
+        .map(|(count, label)| format!("{} {}", label, count))
+        .collect();
         
-        let Ok(entries) = std::fs::read_dir(dir) else {
-            return associated_files;
-        };
+        ui.label(egui::RichText::new(format!("🧠 Filtered out: {} ({})", stats.filtered, parts.join(", ")))
+            .size(11.0)
+            .color(egui::Color32::from_rgb(100, 100, 100)));
+    }
+    
+    fn discard_sandbox(&mut self) {
+        if let Some(root) = self.sandbox_root.take() {
+            let _ = fs::remove_dir_all(root);
+        }
+    }
+    
+    fn archive_files(&mut self, dest: &std::path::Path) {
+        let selected: Vec<usize> = (0..self.scan_results.len())
+            .filter(|&idx| self.scan_results[idx].should_delete)
+            .collect();
+        let blocked = self.protected_among(selected.iter().map(|&idx| &self.scan_results[idx].file_path));
+        if let Some(first) = blocked.first() {
+            self.status_message = format!(
+                "⚠️ Nothing was archived: {} selected files are in protected folders, such as {}",
+                blocked.len(), first.display());
+            return;
+        }
         
-        // Supporting file extensions that should be deleted with .exe
-        let supporting_extensions = [".dll", ".dat", ".ini", ".cfg", ".config"];
+        let needed_bytes: u64 = selected.iter()
+            .filter_map(|&idx| fs::metadata(&self.scan_results[idx].file_path).ok())
+            .map(|m| m.len())
+            .sum();
         
-        for entry in entries {
-            let Ok(entry) = entry else { continue; };
-            let path = entry.path();
-            let file_name = entry.file_name();
-            let file_name_str = file_name.to_str().unwrap_or("");
-            
-            // Skip the .exe itself
-            if path.to_string_lossy() == exe_path {
-                continue;
+        // Check up front so a half-finished move doesn't fill the archive disk
+        match archive::available_space(dest) {
+            Ok(free_bytes) if free_bytes < needed_bytes => {
+                self.status_message = format!(
+                    "❌ Not enough space at {}: need {}, only {} free.",
+                    dest.display(), human_size(needed_bytes), human_size(free_bytes));
+                return;
             }
-            
-            // Check if file name starts with the base name and has a supporting extension
-            let file_lower = file_name_str.to_lowercase();
-            let base_lower = base_name.to_lowercase();
-            
-            if file_lower.starts_with(&base_lower) {
-                for ext in &supporting_extensions {
-                    if file_lower.ends_with(ext) {
-                        associated_files.push(path.to_string_lossy().to_string());
-                        break;
-                    }
-                }
+            Ok(_) => {}
+            Err(e) => {
+                self.status_message = format!("❌ Can't use {} as archive: {}", dest.display(), e);
+                return;
             }
         }
         
-        associated_files
+        let files = selected.iter()
+            .map(|&idx| self.scan_results[idx].file_path.clone())
+            .collect();
+        self.archiving = Some(archive::Archiving::start(files, self.scan_roots.clone(), dest.to_path_buf()));
     }
     
-    fn scan_files(&mut self) {
-        self.is_scanning = true;
-        self.scan_results.clear();
-        self.status_message = "Scanning...".to_string();
-        
-        let user = whoami::username();
-        let working_directory = if cfg!(target_os = "windows") {
-            format!("C:\\Users\\{}\\", user)
-        } else {
-            format!("/Users/{}/", user)
+    fn poll_archiving(&mut self) {
+        let Some(archiving) = &mut self.archiving else {
+            return;
         };
+        if !archiving.poll() {
+            return;
+        }
+        let archiving = self.archiving.take().expect("checked above");
         
-        // Build list of directories to search
-        let mut directories = Vec::new();
-        if self.downloads_enabled {
-            directories.push(format!("{}Downloads", working_directory));
+        let mut moved = HashSet::new();
+        let mut moved_bytes = 0;
+        self.archive_errors.clear();
+        for outcome in archiving.outcomes {
+            match outcome.moved {
+                Ok(size) => {
+                    moved.insert(outcome.path);
+                    moved_bytes += size;
+                }
+                Err(e) => self.archive_errors.push((outcome.path.display().to_string(), e)),
+            }
         }
-        if self.documents_enabled {
-            directories.push(format!("{}Documents", working_directory));
+        
+        self.status_message = format!(
+            "📦 Archived {} files ({}) to {}. ❌ {} failed.",
+            moved.len(), human_size(moved_bytes), archiving.destination.display(), self.archive_errors.len());
+        
+        // Files that failed stay in the list so they can be retried or deleted instead
+        self.scan_results.retain(|result| !moved.contains(&result.file_path));
+        self.result_tree = None;
+        self.refresh_drive_space();
+    }
+    
+    // Selects every matching result, leaving the rest of the selection as it was
+    fn select_where(&mut self, matches: impl Fn(&ScanResult) -> bool) {
+        let mut count = 0;
+        for result in &mut self.scan_results {
+            if !result.kept && matches(result) {
+                result.select(true);
+                count += 1;
+            }
         }
-        if self.desktop_enabled {
-            directories.push(format!("{}Desktop", working_directory));
+        self.status_message = format!("✓ Selected {} matching files.", count);
+    }
+    
+    fn zip_selected_dialog(&mut self) {
+        let file_name = format!("archive-{}.zip", chrono::Local::now().format("%Y%m%d"));
+        let mut dialog = rfd::FileDialog::new()
+            .set_file_name(file_name)
+            .add_filter("Zip", &["zip"]);
+        let destination = self.archive_destination.trim();
+        if !destination.is_empty() {
+            dialog = dialog.set_directory(destination);
         }
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
         
-        // Add custom directories
-        for custom_dir in &self.custom_directories {
-            directories.push(custom_dir.clone());
-        }
+        self.zip_selected(&path);
+    }
+    
+    fn zip_selected(&mut self, zip_path: &std::path::Path) {
+        let files: Vec<PathBuf> = self.scan_results.iter()
+            .filter(|result| result.should_delete)
+            .map(|result| result.file_path.clone())
+            .collect();
+        self.zipping = Some(archive::Zipping::start(files, self.scan_roots.clone(), zip_path.to_path_buf()));
+    }
+    
+    // Originals are only touched once the zip is complete, and only when asked to. They
+    // go through the same confirmation and deletion as anything else.
+    fn poll_zipping(&mut self) {
+        let Some(zipped) = self.zipping.as_ref().and_then(|zipping| zipping.poll()) else {
+            return;
+        };
+        let zipping = self.zipping.take().expect("checked above");
+        let zip_bytes = match zipped {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                self.status_message = format!("❌ Could not create {}: {}", zipping.zip_path.display(), e);
+                return;
+            }
+        };
         
-        let time_limit = std::time::Duration::from_secs(60 * 60 * 24 * self.time_limit_days);
+        let files: HashSet<PathBuf> = zipping.files.into_iter().collect();
+        let original_bytes: u64 = self.scan_results.iter()
+            .filter(|result| files.contains(&result.file_path))
+            .map(|result| result.size_bytes)
+            .sum();
+        self.status_message = format!(
+            "🗜️ Zipped {} files ({}) into {} ({}), saving {}.",
+            files.len(), human_size(original_bytes), zipping.zip_path.display(),
+            human_size(zip_bytes), human_size(original_bytes.saturating_sub(zip_bytes)));
         
-        // Scan each directory recursively
-        for directory_path in directories {
-            self.scan_directory_recursive(&directory_path, time_limit);
+        if self.zip_remove_originals {
+            self.open_delete_confirm(DeleteScope::Zipped(files));
         }
+    }
+    
+    // A deletion, zip or archive is under way, so the list shouldn't change under it
+    fn is_busy(&self) -> bool {
+        self.deletion.is_some() || self.zipping.is_some() || self.archiving.is_some()
+    }
+    
+    fn export_csv_dialog(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("pinnaclesort-results.csv")
+            .add_filter("CSV", &["csv"])
+            .save_file()
+        else {
+            return;
+        };
         
-        self.status_message = format!("Scan complete. Found {} files.", self.scan_results.len());
-        self.is_scanning = false;
+        self.status_message = match self.export_csv(&path) {
+            Ok(()) => format!("📄 Exported {} files to {}.", self.scan_results.len(), path.display()),
+            Err(e) => format!("❌ Could not export to {}: {}", path.display(), e),
+        };
     }
     
-    fn scan_directory_recursive(&mut self, directory_path: &str, time_limit: std::time::Duration) {
-        let Ok(entries) = std::fs::read_dir(directory_path) else {
+    fn export_report_dialog(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("pinnaclesort-report.json")
+            .add_filter("JSON", &["json"])
+            .save_file()
+        else {
             return;
         };
         
-        for entry in entries {
-            let Ok(entry) = entry else { continue; };
-            let file_name = entry.file_name();
-            let file_name_str = file_name.to_str().unwrap_or("").to_string();
-            let path = entry.path();
-            
-            // Skip hidden files and directories
-            if file_name_str.starts_with('.') {
-                continue;
-            }
-            
-            // If it's a directory, recurse into it
-            if path.is_dir() {
-                self.scan_directory_recursive(&path.to_string_lossy(), time_limit);
-                continue;
-            }
-            
-            // Apply smart filter to exclude binary/system files
-            if self.should_exclude_file(&file_name_str) {
-                continue;
+        self.status_message = match report::write(&path, &self.settings(), &self.scan_roots, &self.scan_results) {
+            Ok(()) => format!("🧾 Wrote a report of {} files to {}.", self.scan_results.len(), path.display()),
+            Err(e) => format!("❌ Could not write the report to {}: {}", path.display(), e),
+        };
+    }
+    
+    fn export_csv(&self, path: &std::path::Path) -> std::io::Result<()> {
+        let mut csv = String::from("file_path,file_name,size_bytes,days_since_access,selected\n");
+        for result in &self.scan_results {
+            csv += &format!(
+                "{},{},{},{},{}\n",
+                csv_field(&result.file_path.to_string_lossy()),
+                csv_field(&result.file_name),
+                result.size_bytes,
+                result.days_since_access,
+                result.should_delete,
+            );
+        }
+        fs::write(path, csv)
+    }
+    
+    // Walks up from the folders files were deleted from, deepest first, so a folder
+    // whose only contents were emptied subfolders goes too. Stops at the scan roots.
+    fn remove_empty_folders(&self, folders: HashSet<PathBuf>) -> usize {
+        let mut candidates = HashSet::new();
+        for folder in folders {
+            for ancestor in folder.ancestors() {
+                let inside_root = self.scan_roots.iter()
+                    .any(|root| ancestor.starts_with(root) && ancestor != root.as_path());
+                if !inside_root || !candidates.insert(ancestor.to_path_buf()) {
+                    break;
+                }
             }
-            
-            // Get metadata and accessed time
-            let Ok(metadata) = fs::metadata(&path) else {
-                continue;
-            };
-            
-            let Ok(accessed) = metadata.accessed() else {
-                continue;
-            };
-            
-            let recently_accessed = accessed >= std::time::SystemTime::now() - time_limit;
-            
-            if !recently_accessed {
-                // Calculate days since access
-                let duration = std::time::SystemTime::now()
-                    .duration_since(accessed)
-                    .unwrap_or_default();
-                let days_since_access = duration.as_secs() / (60 * 60 * 24);
-                
-                self.scan_results.push(ScanResult {
-                    file_path: path.to_string_lossy().to_string(),
-                    file_name: file_name_str,
-                    should_delete: true,
-                    days_since_access,
-                });
+        }
+        
+        let mut candidates: Vec<PathBuf> = candidates.into_iter().collect();
+        candidates.sort_by_key(|folder| std::cmp::Reverse(folder.components().count()));
+        
+        // remove_dir refuses anything that isn't empty, which is exactly the check we want
+        candidates.iter()
+            .filter(|folder| fs::remove_dir(folder).is_ok())
+            .count()
+    }
+    
+    // Hands the results `pick` chooses to a worker thread. A dry run goes through
+    // the same steps without removing anything and leaves the results in place.
+    // The scan never reports protected files, but the list may have changed since
+    fn protected_among<'a>(&self, paths: impl Iterator<Item = &'a PathBuf>) -> Vec<&'a PathBuf> {
+        let protected = scanner::protected_prefixes(&self.protected_paths);
+        paths.filter(|path| scanner::is_protected(path, &protected)).collect()
+    }
+    
+    fn start_deletion(&mut self, to_trash: bool, scope: &DeleteScope) {
+        let marked_folders = if scope.takes_marked_folders() { self.marked_folders.clone() } else { BTreeSet::new() };
+        let blocked = self.protected_among(self.scan_results.iter()
+            .filter(|result| scope.includes(result))
+            .map(|result| &result.file_path)
+            .chain(marked_folders.iter()));
+        if let Some(first) = blocked.first() {
+            self.status_message = format!(
+                "⚠️ Nothing was deleted: {} selected files are in protected folders, such as {}",
+                blocked.len(), first.display());
+            return;
+        }
+        
+        // Marked folders go first and take any selected files inside them along.
+        // If a file has the trigger extension, its associated files go before it.
+        let in_marked_folder = |path: &Path| marked_folders.iter().any(|folder| path.starts_with(folder));
+        let targets = marked_folders.iter()
+            .map(|folder| deletion::DeleteTarget {
+                path: folder.clone(),
+                size_bytes: None,
+                associated: Vec::new(),
+            })
+            .chain(self.scan_results.iter()
+                .filter(|result| scope.includes(result) && !in_marked_folder(&result.file_path))
+                .map(|result| deletion::DeleteTarget {
+                    path: result.file_path.clone(),
+                    size_bytes: Some(result.size_bytes),
+                    associated: self.find_associated_files(&result.file_path),
+                }))
+            .collect();
+        let options = deletion::DeleteOptions {
+            to_trash,
+            secure: self.secure_delete && !to_trash,
+            force_read_only: self.force_read_only,
+            dry_run: self.dry_run,
+            trash_directories: if self.trash_scan_enabled { trash_bin::trash_directories() } else { Vec::new() },
+        };
+        self.deletion = Some(deletion::Deletion::start(targets, options));
+    }
+    
+    // Takes in what the worker has removed since the last frame, and wraps up once it's done
+    fn poll_deletion(&mut self) {
+        let Some(deletion) = &mut self.deletion else {
+            return;
+        };
+        if deletion.poll() {
+            let deletion = self.deletion.take().expect("checked above");
+            let freed_bytes = self.finish_deletion(deletion);
+            if let Some(QuickClean::Deleting { before_bytes }) = self.quick_clean {
+                let after_bytes = before_bytes.saturating_sub(freed_bytes);
+                self.quick_clean = Some(QuickClean::Done { before_bytes, after_bytes });
             }
         }
     }
     
-    fn delete_files(&mut self) {
+    // Returns how many bytes the removed files took up. Whatever wasn't removed,
+    // because it failed or the deletion was cancelled, stays in the list to retry.
+    fn finish_deletion(&mut self, deletion: deletion::Deletion) -> u64 {
+        let options = &deletion.options;
+        let mut freed_bytes = 0;
         let mut deleted_count = 0;
         let mut failed_count = 0;
         let mut associated_deleted = 0;
+        let mut folders_deleted = 0;
+        let mut forced_count = 0;
+        let mut removed_paths = HashSet::new();
+        let mut failures = HashMap::new();
+        let mut removals = Vec::new();
+        // Associated files that stayed when their result went get rows of their own,
+        // so they can be retried
+        let mut left_behind = Vec::new();
         
-        for result in &self.scan_results {
-            if result.should_delete {
-                // If it's an .exe file, find and delete associated files first
-                if result.file_path.to_lowercase().ends_with(".exe") {
-                    let associated_files = self.find_associated_files(&result.file_path);
-                    for assoc_file in associated_files {
-                        if fs::remove_file(&assoc_file).is_ok() {
-                            associated_deleted += 1;
-                        }
-                    }
+        for outcome in deletion.outcomes.iter() {
+            forced_count += outcome.forced;
+            associated_deleted += outcome.associated.len();
+            removals.extend(outcome.associated.iter().cloned());
+            failed_count += outcome.associated_failed.len();
+            let source = self.scan_results.iter().find(|result| result.file_path == outcome.path);
+            for (path, error) in &outcome.associated_failed {
+                if let Some(source) = source {
+                    let size_bytes = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+                    left_behind.push(ScanResult {
+                        file_path: path.clone(),
+                        file_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                        should_delete: false,
+                        size_bytes,
+                        use_count: None,
+                        flag_reason: format!("Goes with {}", source.file_name),
+                        incomplete_download: false,
+                        empty: size_bytes == 0,
+                        broken_symlink: false,
+                        duplicate_group: None,
+                        delete_error: Some(error.clone()),
+                        ..source.clone()
+                    });
                 }
-                
-                // Delete the main file
-                match fs::remove_file(&result.file_path) {
-                    Ok(_) => deleted_count += 1,
-                    Err(_) => failed_count += 1,
+            }
+            match &outcome.removed {
+                Ok(removal) if self.marked_folders.contains(&outcome.path) => {
+                    folders_deleted += 1;
+                    freed_bytes += removal.size_bytes;
+                    removed_paths.insert(outcome.path.clone());
+                    removals.push(removal.clone());
+                }
+                Ok(removal) => {
+                    deleted_count += 1;
+                    freed_bytes += removal.size_bytes;
+                    removed_paths.insert(outcome.path.clone());
+                    removals.push(removal.clone());
+                }
+                Err(error) => {
+                    failed_count += 1;
+                    failures.insert(outcome.path.clone(), error.clone());
                 }
             }
         }
         
+        let verb = if options.to_trash {
+            "Moved to Trash"
+        } else if options.secure {
+            "Securely deleted"
+        } else {
+            "Deleted"
+        };
         let message = if associated_deleted > 0 {
             format!(
-                "✅ Deleted {} files ({} associated files). ❌ {} failed.",
-                deleted_count, associated_deleted, failed_count
+                "✅ {} {} files ({} associated files). ❌ {} failed.",
+                verb, deleted_count, associated_deleted, failed_count
             )
         } else {
             format!(
-                "✅ Deleted {} files. ❌ {} failed.",
-                deleted_count, failed_count
+                "✅ {} {} files. ❌ {} failed.",
+                verb, deleted_count, failed_count
             )
         };
         
-        self.status_message = message;
-        self.scan_results.clear();
+        let message = if folders_deleted > 0 {
+            format!("{} 🗂 {} whole folders went too.", message, folders_deleted)
+        } else {
+            message
+        };
+        
+        let message = if deletion.outcomes.len() < deletion.total {
+            format!("{} ⏹ Cancelled with {} files left.", message, deletion.total - deletion.outcomes.len())
+        } else {
+            message
+        };
+        
+        let message = if forced_count > 0 {
+            format!("{} 🔓 {} were read-only and force deleted.", message, forced_count)
+        } else {
+            message
+        };
+        
+        let message = if !options.dry_run && self.remove_emptied_folders {
+            let emptied = removals.iter().filter_map(|removal| removal.path.parent().map(PathBuf::from)).collect();
+            match self.remove_empty_folders(emptied) {
+                0 => message,
+                removed => format!("{} 📁 Removed {} empty folders.", message, removed),
+            }
+        } else {
+            message
+        };
+        
+        let message = if options.dry_run { format!("[DRY RUN] {}", message) } else { message };
+        
+        self.status_message = match &self.sandbox_root {
+            Some(root) => format!("🧪 [SANDBOX {}] {}", root.display(), message),
+            None => message,
+        };
+        
+        if options.dry_run {
+            self.dry_run_log = removals.iter().map(|removal| removal.path.clone()).collect();
+            return freed_bytes;
+        }
+        self.dry_run_log.clear();
+        // Removed folders take every result inside them along
+        self.scan_results.retain(|result| {
+            !result.file_path.ancestors().any(|path| removed_paths.contains(path))
+        });
+        self.marked_folders.retain(|folder| !removed_paths.contains(folder));
+        self.scan_results.extend(left_behind);
+        for result in &mut self.scan_results {
+            if let Some(error) = failures.remove(&result.file_path) {
+                result.delete_error = Some(error);
+            }
+        }
+        self.result_tree = None;
+        
+        if let Err(e) = history::append(&removals, self.history_max_kb * 1024) {
+            self.status_message = format!("{} ⚠️ Could not write the deletion history: {}", self.status_message, e);
+        }
+        
+        // Only a trashed batch can be undone
+        let trashed: Vec<PathBuf> = removals.iter()
+            .filter(|removal| removal.trashed)
+            .map(|removal| removal.path.clone())
+            .collect();
+        if !trashed.is_empty() {
+            self.last_deleted = trashed;
+            self.last_deleted_at = Some(std::time::Instant::now());
+        } else {
+            self.last_deleted.clear();
+            self.last_deleted_at = None;
+        }
+        
+        if self.trash_report_enabled {
+            self.refresh_trash();
+        }
+        self.refresh_drive_space();
+        
+        freed_bytes
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folders_nest_under_their_parents() {
+        // C:\Users\me\Downloads on Windows, /Users/me/Downloads elsewhere
+        let root = if cfg!(windows) { PathBuf::from(r"C:\") } else { PathBuf::from("/") };
+        let downloads = root.join("Users").join("me").join("Downloads");
+        let file = downloads.join("a").join("x.zip");
+
+        let tree = folder_tree(&[file.parent().unwrap().to_path_buf()]);
+        let children = &tree[&downloads.to_string_lossy().to_string()];
+        assert_eq!(children, &[downloads.join("a").to_string_lossy().to_string()]);
+        assert!(tree[&root.to_string_lossy().to_string()].contains(&root.join("Users").to_string_lossy().to_string()));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_folders_nest_under_their_drive_paths() {
+        let tree = folder_tree(&[PathBuf::from(r"C:\Users\me\Downloads\a")]);
+        assert_eq!(tree[r"C:\Users\me\Downloads"], [r"C:\Users\me\Downloads\a"]);
+        assert_eq!(tree[r"C:\Users\me"], [r"C:\Users\me\Downloads"]);
+        assert_eq!(tree[r"C:\"], [r"C:\Users"]);
+    }}
@@ -0,0 +1,102 @@
+// JSON report of a scan, for dashboards and scripts. Consumers rely on the shape,
+// so fields are only ever added; anything that would break a reader (renaming,
+// removing, changing a type) bumps SCHEMA_VERSION.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::scanner::{ScanResult, TimeBasis, TimeCombiner, TimeUnit};
+use crate::settings::Settings;
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Report<'a> {
+    schema_version: u32,
+    app_version: &'static str,
+    // RFC 3339, local time with offset
+    generated_at: String,
+    settings: ReportSettings<'a>,
+    directories: Vec<String>,
+    files: Vec<ReportFile<'a>>,
+}
+
+// The settings that decide what a scan finds, not the whole config file
+#[derive(Serialize)]
+struct ReportSettings<'a> {
+    time_limit: u64,
+    time_unit: TimeUnit,
+    time_basis: TimeBasis,
+    time_combiner: TimeCombiner,
+    smart_filter_enabled: bool,
+    preselect_results: bool,
+    include_hidden: bool,
+    follow_symlinks: bool,
+    max_depth: Option<usize>,
+    min_size_mb: Option<u64>,
+    max_size_mb: Option<u64>,
+    duplicates_enabled: bool,
+    exclude_patterns: &'a [String],
+    include_extensions: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ReportFile<'a> {
+    path: String,
+    size_bytes: u64,
+    days_since_access: u64,
+    selected: bool,
+    kept: bool,
+    reason: &'a str,
+    duplicate_group: Option<usize>,
+    empty: bool,
+    broken_symlink: bool,
+}
+
+pub fn write(
+    path: &Path,
+    settings: &Settings,
+    directories: &[PathBuf],
+    results: &[ScanResult],
+) -> Result<(), String> {
+    let report = Report {
+        schema_version: SCHEMA_VERSION,
+        app_version: env!("CARGO_PKG_VERSION"),
+        generated_at: chrono::Local::now().to_rfc3339(),
+        settings: ReportSettings {
+            time_limit: settings.time_limit,
+            time_unit: settings.time_unit,
+            time_basis: settings.time_basis,
+            time_combiner: settings.time_combiner,
+            smart_filter_enabled: settings.smart_filter_enabled,
+            preselect_results: settings.preselect_results,
+            include_hidden: settings.include_hidden,
+            follow_symlinks: settings.follow_symlinks,
+            max_depth: settings.max_depth,
+            min_size_mb: settings.min_size_mb,
+            max_size_mb: settings.max_size_mb,
+            duplicates_enabled: settings.duplicates_enabled,
+            exclude_patterns: &settings.exclude_patterns,
+            include_extensions: crate::scanner::extension_list(&settings.include_extensions),
+        },
+        directories: directories.iter().map(|dir| dir.to_string_lossy().to_string()).collect(),
+        files: results
+            .iter()
+            .map(|result| ReportFile {
+                path: result.file_path.to_string_lossy().to_string(),
+                size_bytes: result.size_bytes,
+                days_since_access: result.days_since_access,
+                selected: result.should_delete,
+                kept: result.kept,
+                reason: &result.flag_reason,
+                duplicate_group: result.duplicate_group,
+                empty: result.empty,
+                broken_symlink: result.broken_symlink,
+            })
+            .collect(),
+    };
+
+    let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
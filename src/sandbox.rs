@@ -0,0 +1,69 @@
+// Throwaway copies of scan targets so the full scan+delete flow can be tried safely
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Creates an empty, uniquely named sandbox directory under the system temp dir
+pub fn create_sandbox() -> io::Result<PathBuf> {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let root = std::env::temp_dir().join(format!("pinnaclesort-sandbox-{}", stamp));
+    fs::create_dir_all(&root)?;
+    Ok(root)
+}
+
+// Recursively copies `source` to `dest`, returning how many files were copied.
+// Timestamps are carried over so the copy ages exactly like the original, and the
+// original's access time is put back since reading it to copy would refresh it.
+pub fn copy_tree(source: &Path, dest: &Path) -> io::Result<usize> {
+    fs::create_dir_all(dest)?;
+    let mut copied = 0;
+
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let from = entry.path();
+        let to = dest.join(entry.file_name());
+
+        // Symlinks are skipped so a link back up the tree can't recurse forever
+        if file_type.is_symlink() {
+            continue;
+        }
+
+        if file_type.is_dir() {
+            copied += copy_tree(&from, &to)?;
+            continue;
+        }
+
+        let metadata = entry.metadata()?;
+        let accessed = filetime::FileTime::from_last_access_time(&metadata);
+        let modified = filetime::FileTime::from_last_modification_time(&metadata);
+
+        fs::copy(&from, &to)?;
+        filetime::set_file_times(&to, accessed, modified)?;
+        let _ = filetime::set_file_atime(&from, accessed);
+        copied += 1;
+    }
+
+    Ok(copied)
+}
+
+// Where each scan target goes inside the sandbox, as (source, copy) pairs.
+// Targets that aren't directories are left out.
+pub fn sandbox_targets(root: &Path, directories: &[String]) -> Vec<(PathBuf, PathBuf)> {
+    directories
+        .iter()
+        .enumerate()
+        .map(|(idx, directory)| (idx, Path::new(directory)))
+        .filter(|(_, source)| source.is_dir())
+        .map(|(idx, source)| {
+            let name = source.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "root".to_string());
+            (source.to_path_buf(), root.join(format!("{}-{}", idx, name)))
+        })
+        .collect()
+}
@@ -0,0 +1,69 @@
+// The last scan's results, saved on exit so a review can pick up where it left off
+// after a restart. Files can change in between, so everything is checked again on
+// the way back in.
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::scanner::ScanResult;
+
+#[derive(Serialize, Deserialize)]
+pub struct ScanCache {
+    // RFC 3339, local time with offset
+    pub saved_at: String,
+    pub directories: Vec<PathBuf>,
+    pub results: Vec<ScanResult>,
+}
+
+// e.g. ~/.config/pinnaclesort/last_scan.json on Linux
+fn cache_path() -> Option<PathBuf> {
+    Some(crate::settings::config_dir()?.join("last_scan.json"))
+}
+
+// Paths that aren't valid UTF-8 can't go into JSON, so those few are left out
+pub fn save(directories: &[PathBuf], results: &[ScanResult]) -> Result<(), String> {
+    let path = cache_path().ok_or_else(|| "no config directory for this user".to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let cache = ScanCache {
+        saved_at: chrono::Local::now().to_rfc3339(),
+        directories: directories.iter().filter(|d| d.to_str().is_some()).cloned().collect(),
+        results: results.iter().filter(|r| r.file_path.to_str().is_some()).cloned().collect(),
+    };
+    let contents = serde_json::to_string(&cache).map_err(|e| e.to_string())?;
+    fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+// A missing or unreadable cache just means there's nothing to offer
+pub fn load() -> Option<ScanCache> {
+    let contents = fs::read_to_string(cache_path()?).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn clear() {
+    if let Some(path) = cache_path() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+impl ScanCache {
+    // Drops files that are gone and picks up new sizes for the rest. Returns how many
+    // were dropped.
+    pub fn revalidate(&mut self) -> usize {
+        let before = self.results.len();
+        self.results.retain_mut(|result| match fs::symlink_metadata(&result.file_path) {
+            Ok(metadata) => {
+                if metadata.is_file() {
+                    result.size_bytes = metadata.len();
+                }
+                true
+            }
+            Err(_) => false,
+        });
+        before - self.results.len()
+    }
+}
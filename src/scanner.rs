@@ -0,0 +1,1151 @@
+// Directory scanning, run on a worker thread and streamed back to the UI
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::cleanignore::{self, IgnoreStack};
+use crate::volumes::{ExternalVolumes, VolumeKind};
+use crate::{duplicates, sandbox, usage};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScanResult {
+    pub file_path: PathBuf,
+    // Display only; names that aren't valid UTF-8 get replacement characters here
+    pub file_name: String,
+    pub should_delete: bool,
+    pub days_since_access: u64,
+    // The timestamp the age was judged by, so dates can be shown without a rescan
+    pub last_used: SystemTime,
+    pub size_bytes: u64,
+    pub use_count: Option<u64>,
+    pub flag_reason: String,
+    pub incomplete_download: bool,
+    // Zero-byte files and links whose target is gone, which are almost always safe to remove
+    pub empty: bool,
+    pub broken_symlink: bool,
+    // Files with identical contents share a group number
+    pub duplicate_group: Option<usize>,
+    // On the never-delete list, so it's shown but can't be selected
+    pub kept: bool,
+    // Set for files on a network or removable drive
+    pub volume: Option<VolumeKind>,
+    // Why the last attempt to remove it failed
+    pub delete_error: Option<String>,
+}
+
+impl ScanResult {
+    // Every bulk selection goes through here so kept files stay unselected
+    pub fn select(&mut self, select: bool) {
+        self.should_delete = select && !self.kept;
+    }
+}
+
+// Which timestamp decides how old a file is. Access times are often disabled
+// (noatime, network shares), which makes everything look old.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TimeBasis {
+    Accessed,
+    Modified,
+    Created,
+}
+
+impl TimeBasis {
+    pub const ALL: [TimeBasis; 3] = [TimeBasis::Accessed, TimeBasis::Modified, TimeBasis::Created];
+
+    pub fn verb(self) -> &'static str {
+        match self {
+            TimeBasis::Accessed => "accessed",
+            TimeBasis::Modified => "modified",
+            TimeBasis::Created => "created",
+        }
+    }
+
+    fn read(self, metadata: &fs::Metadata) -> std::io::Result<SystemTime> {
+        match self {
+            TimeBasis::Accessed => metadata.accessed(),
+            TimeBasis::Modified => metadata.modified(),
+            TimeBasis::Created => metadata.created(),
+        }
+    }
+
+    // The chosen timestamp, or else the first of modified and created time the
+    // filesystem has, along with which one it turned out to be. `read` is usually
+    // `|basis| basis.read(&metadata)`.
+    fn read_or_fallback(
+        self,
+        read: impl Fn(TimeBasis) -> std::io::Result<SystemTime>,
+    ) -> Option<(SystemTime, TimeBasis)> {
+        [self, TimeBasis::Modified, TimeBasis::Created]
+            .into_iter()
+            .find_map(|basis| Some((read(basis).ok()?, basis)))
+    }
+}
+
+// Whether age comes from the chosen timestamp alone, or from access and modified
+// time together. Both: neither happened within the limit. Either: at least one didn't.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TimeCombiner {
+    Single,
+    Both,
+    Either,
+}
+
+impl TimeCombiner {
+    pub const ALL: [TimeCombiner; 3] = [TimeCombiner::Single, TimeCombiner::Both, TimeCombiner::Either];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TimeCombiner::Single => "that timestamp only",
+            TimeCombiner::Both => "both accessed and modified",
+            TimeCombiner::Either => "either accessed or modified",
+        }
+    }
+
+    pub fn verb(self, basis: TimeBasis) -> &'static str {
+        match self {
+            TimeCombiner::Single => basis.verb(),
+            TimeCombiner::Both => "accessed or modified",
+            TimeCombiner::Either => "accessed, or not modified,",
+        }
+    }
+}
+
+// What the age threshold is counted in. Months and years are the calendar-free
+// 30 and 365 days, which is close enough for "haven't touched it in ages".
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TimeUnit {
+    Hours,
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+impl TimeUnit {
+    pub const ALL: [TimeUnit; 5] = [TimeUnit::Hours, TimeUnit::Days, TimeUnit::Weeks, TimeUnit::Months, TimeUnit::Years];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TimeUnit::Hours => "hours",
+            TimeUnit::Days => "days",
+            TimeUnit::Weeks => "weeks",
+            TimeUnit::Months => "months",
+            TimeUnit::Years => "years",
+        }
+    }
+
+    // Largest amount the slider offers, roughly a year or two in each unit
+    pub fn max_amount(self) -> u64 {
+        match self {
+            TimeUnit::Hours => 168,
+            TimeUnit::Days => 365,
+            TimeUnit::Weeks => 104,
+            TimeUnit::Months => 36,
+            TimeUnit::Years => 10,
+        }
+    }
+
+    pub fn duration(self, amount: u64) -> Duration {
+        let hours: u64 = match self {
+            TimeUnit::Hours => 1,
+            TimeUnit::Days => 24,
+            TimeUnit::Weeks => 24 * 7,
+            TimeUnit::Months => 24 * 30,
+            TimeUnit::Years => 24 * 365,
+        };
+        Duration::from_secs((60 * 60 * hours).saturating_mul(amount))
+    }
+}
+
+// What the smart filter leaves out. Stored one pattern per entry exactly as typed,
+// so blank lines and stray spaces are dropped when the patterns are read.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterRules {
+    pub binary_extensions: Vec<String>,
+    pub system_patterns: Vec<String>,
+    pub build_patterns: Vec<String>,
+}
+
+impl Default for FilterRules {
+    fn default() -> Self {
+        let owned = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect();
+        Self {
+            // .exe is left out on purpose, old installers are worth flagging
+            binary_extensions: owned(&[
+                ".dll", ".so", ".dylib", ".bin", ".o", ".a",
+                ".lib", ".sys", ".drv", ".class", ".pyc", ".pyo",
+            ]),
+            system_patterns: owned(&[
+                ".cache", ".tmp", ".temp", ".log", ".bak", ".swp", ".swo",
+                ".lock", ".pid", ".dat", ".db", ".sqlite", ".idx",
+            ]),
+            build_patterns: owned(&[
+                "node_modules", "target", "build", "dist", ".git", ".svn",
+            ]),
+        }
+    }
+}
+
+impl FilterRules {
+    pub fn binary_extensions(&self) -> impl Iterator<Item = String> + '_ {
+        Self::patterns(&self.binary_extensions)
+    }
+
+    pub fn system_patterns(&self) -> impl Iterator<Item = String> + '_ {
+        Self::patterns(&self.system_patterns)
+    }
+
+    pub fn build_patterns(&self) -> impl Iterator<Item = String> + '_ {
+        Self::patterns(&self.build_patterns)
+    }
+
+    fn patterns(lines: &[String]) -> impl Iterator<Item = String> + '_ {
+        lines.iter()
+            .map(|line| line.trim().to_lowercase())
+            .filter(|line| !line.is_empty())
+    }
+}
+
+// Snapshot of the settings a scan needs, so the worker doesn't borrow the app
+#[derive(Clone)]
+pub struct ScanOptions {
+    pub time_limit: Duration,
+    pub time_basis: TimeBasis,
+    pub time_combiner: TimeCombiner,
+    // Files modified within this long are never reported, whatever the time basis
+    pub protect_modified_within: Option<Duration>,
+    pub smart_filter_enabled: bool,
+    pub filter_rules: FilterRules,
+    pub preselect_results: bool,
+    pub usage_signal_enabled: bool,
+    pub frequent_use_count: u64,
+    pub large_dir_threshold: usize,
+    pub large_dirs_allowed: HashSet<PathBuf>,
+    pub incomplete_downloads_enabled: bool,
+    pub incomplete_download_days: u64,
+    pub follow_symlinks: bool,
+    // Dotfiles, and on Windows files with the hidden attribute, are skipped unless set
+    pub include_hidden: bool,
+    // How many levels below each scan target to descend; 0 is the target's own files only
+    pub max_depth: Option<usize>,
+    pub duplicates_enabled: bool,
+    // Files and directories whose full path matches are skipped, and not descended into
+    pub exclude: GlobSet,
+    // Inside git projects, skip what their .gitignore files leave out
+    pub respect_gitignore: bool,
+    // Files outside these bounds are ignored; None leaves that side open
+    pub min_size_bytes: Option<u64>,
+    pub max_size_bytes: Option<u64>,
+    // When not empty, only files with one of these extensions (lowercase, without
+    // the dot) are reported, in place of the smart filter
+    pub include_extensions: Vec<String>,
+    // Nothing under these is scanned, even when a scan target sits inside one
+    pub protected: Vec<PathBuf>,
+    pub keep: KeepList,
+    // Set by the Junk preset: only files whose name matches are reported, and the
+    // smart filter and hidden-file rules don't apply to them
+    pub junk: Option<GlobSet>,
+    // Send every file the smart filter drops, so the rules can be checked
+    pub list_filtered: bool,
+}
+
+// Files the user never wants deleted: paths marked Keep one at a time, and
+// wildcard patterns matched against the full path
+#[derive(Clone, Default)]
+pub struct KeepList {
+    paths: HashSet<PathBuf>,
+    patterns: GlobSet,
+}
+
+impl KeepList {
+    pub fn new(paths: &HashSet<String>, patterns: &[String]) -> Self {
+        Self {
+            paths: paths.iter().map(PathBuf::from).collect(),
+            patterns: exclude_globs(patterns).0,
+        }
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.contains(path) || self.patterns.is_match(path)
+    }
+}
+
+impl ScanOptions {
+    pub fn is_protected(&self, path: &Path) -> bool {
+        is_protected(path, &self.protected)
+    }
+
+    fn is_included(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|ext| {
+            self.include_extensions.contains(&ext.to_string_lossy().to_lowercase())
+        })
+    }
+
+    fn is_junk(&self, file_name: &str) -> bool {
+        self.junk.as_ref().is_some_and(|junk| junk.is_match(file_name))
+    }
+
+    // Hidden entries are skipped unless asked for, or they're junk like .DS_Store
+    fn skips_hidden(&self, entry: &fs::DirEntry) -> bool {
+        !self.include_hidden && is_hidden(entry) && !self.is_junk(&entry.file_name().to_string_lossy())
+    }
+}
+
+// Counters shared with the UI. `total` is filled in by a counting pass before the
+// real scan starts, so it stays at zero while `counting` is set. Setting `cancelled`
+// makes the worker stop at the next entry and finish with what it has so far.
+#[derive(Default)]
+pub struct ScanProgress {
+    pub counting: AtomicBool,
+    pub cancelled: AtomicBool,
+    pub total: AtomicUsize,
+    pub processed: AtomicUsize,
+}
+
+impl ScanProgress {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    // Fraction done, or None while the total isn't known yet
+    pub fn fraction(&self) -> Option<f32> {
+        if self.counting.load(Ordering::Relaxed) {
+            return None;
+        }
+        let total = self.total.load(Ordering::Relaxed);
+        let processed = self.processed.load(Ordering::Relaxed);
+        Some(if total == 0 { 1.0 } else { (processed as f32 / total as f32).min(1.0) })
+    }
+}
+
+pub enum ScanEvent {
+    Found(ScanResult),
+    LargeDirectory(PathBuf, usize),
+    // A path that couldn't be read, with the reason; the scan carries on without it
+    Unreadable(String, String),
+    // Only sent when `list_filtered` is set
+    Filtered(PathBuf, FilterCategory),
+    Failed(String),
+    Finished(ScanStats),
+}
+
+// Which of the smart filter's rules dropped a file
+#[derive(Clone, Copy, PartialEq)]
+pub enum FilterCategory {
+    Binary,
+    System,
+    Build,
+}
+
+impl FilterCategory {
+    pub fn label(self) -> &'static str {
+        match self {
+            FilterCategory::Binary => "binary",
+            FilterCategory::System => "system",
+            FilterCategory::Build => "build",
+        }
+    }
+}
+
+// What a finished scan went through, for the status line
+#[derive(Clone, Copy, Default)]
+pub struct ScanStats {
+    pub elapsed: Duration,
+    pub bytes_scanned: u64,
+    pub directories: usize,
+    // Entries skipped by the exclude patterns, files dropped by the smart filter (or
+    // not on the junk list), and files too recently used to be flagged
+    pub excluded: usize,
+    pub filtered: usize,
+    pub too_recent: usize,
+    // The part of `filtered` each smart filter rule accounts for
+    pub filtered_binary: usize,
+    pub filtered_system: usize,
+    pub filtered_build: usize,
+    // Files judged by another timestamp because the chosen one wasn't available
+    pub timestamp_fallbacks: usize,
+    // Links whose target is gone
+    pub broken_symlinks: usize,
+}
+
+// Copies `copies` (source, destination) first when running in sandbox mode, then
+// scans `directories`, sending each result as soon as it is found
+pub fn spawn_scan(
+    directories: Vec<PathBuf>,
+    copies: Vec<(PathBuf, PathBuf)>,
+    options: ScanOptions,
+    progress: Arc<ScanProgress>,
+) -> Receiver<ScanEvent> {
+    let (sender, receiver) = mpsc::channel();
+    progress.counting.store(true, Ordering::Relaxed);
+
+    std::thread::spawn(move || {
+        let started = Instant::now();
+        for (source, dest) in &copies {
+            if let Err(e) = sandbox::copy_tree(source, dest) {
+                let _ = sender.send(ScanEvent::Failed(format!("Could not create sandbox copy: {}", e)));
+                return;
+            }
+        }
+
+        let (ignores, errors) = IgnoreStack::global(options.respect_gitignore);
+        for (path, error) in errors {
+            let _ = sender.send(ScanEvent::Unreadable(path.display().to_string(), error));
+        }
+
+        let counted = Mutex::new(HashSet::new());
+        directories.par_iter().for_each(|directory_path| {
+            let (ignores, _) = ignores.for_target(directory_path);
+            count_files(directory_path, 0, &options, &progress, &counted, &ignores);
+        });
+        progress.counting.store(false, Ordering::Relaxed);
+
+        let scanner = Scanner {
+            options: &options,
+            sender: &sender,
+            progress: &progress,
+            now: SystemTime::now(),
+            bytes_scanned: AtomicU64::new(0),
+            directories: AtomicUsize::new(0),
+            excluded: AtomicUsize::new(0),
+            filtered: AtomicUsize::new(0),
+            too_recent: AtomicUsize::new(0),
+            filtered_by: Default::default(),
+            timestamp_fallbacks: AtomicUsize::new(0),
+            broken_symlinks: AtomicUsize::new(0),
+            visited: Mutex::new(HashSet::new()),
+            hashable: Mutex::new(Vec::new()),
+            volumes: ExternalVolumes::detect(),
+        };
+
+        directories.par_iter().for_each(|directory_path| {
+            let (ignores, errors) = ignores.for_target(directory_path);
+            for (path, error) in errors {
+                let _ = sender.send(ScanEvent::Unreadable(path.display().to_string(), error));
+            }
+            scanner.scan_directory_recursive(directory_path, 0, &ignores);
+        });
+
+        if options.duplicates_enabled && !progress.is_cancelled() {
+            scanner.report_duplicates();
+        }
+
+        let _ = sender.send(ScanEvent::Finished(ScanStats {
+            elapsed: started.elapsed(),
+            bytes_scanned: scanner.bytes_scanned.into_inner(),
+            directories: scanner.directories.into_inner(),
+            excluded: scanner.excluded.into_inner(),
+            filtered: scanner.filtered.into_inner(),
+            too_recent: scanner.too_recent.into_inner(),
+            filtered_binary: scanner.filtered_by[FilterCategory::Binary as usize].load(Ordering::Relaxed),
+            filtered_system: scanner.filtered_by[FilterCategory::System as usize].load(Ordering::Relaxed),
+            filtered_build: scanner.filtered_by[FilterCategory::Build as usize].load(Ordering::Relaxed),
+            timestamp_fallbacks: scanner.timestamp_fallbacks.into_inner(),
+            broken_symlinks: scanner.broken_symlinks.into_inner(),
+        }));
+    });
+
+    receiver
+}
+
+struct Scanner<'a> {
+    options: &'a ScanOptions,
+    sender: &'a Sender<ScanEvent>,
+    progress: &'a ScanProgress,
+    now: SystemTime,
+    bytes_scanned: AtomicU64,
+    directories: AtomicUsize,
+    excluded: AtomicUsize,
+    filtered: AtomicUsize,
+    too_recent: AtomicUsize,
+    // Indexed by FilterCategory
+    filtered_by: [AtomicUsize; 3],
+    timestamp_fallbacks: AtomicUsize,
+    broken_symlinks: AtomicUsize,
+    // Canonical paths of directories already entered, so a symlink back up the tree
+    // (or two links to the same place) is only scanned once
+    visited: Mutex<HashSet<PathBuf>>,
+    // (path, size, when last used, already reported for its age) of every file that
+    // made it past the filters, for the duplicate pass
+    hashable: Mutex<Vec<(PathBuf, u64, SystemTime, bool)>>,
+    volumes: ExternalVolumes,
+}
+
+impl Scanner<'_> {
+    fn scan_directory_recursive(&self, directory_path: &Path, depth: usize, ignores: &IgnoreStack) {
+        if self.progress.is_cancelled()
+            || self.options.is_protected(directory_path)
+            || !first_visit(directory_path, &self.visited)
+        {
+            return;
+        }
+
+        let entries = match std::fs::read_dir(directory_path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.unreadable(directory_path, &e);
+                return;
+            }
+        };
+        let entries: Vec<_> = entries.collect();
+
+        // Huge directories (media libraries, dependency caches) are held back until
+        // the user explicitly asks to descend into them
+        if entries.len() > self.options.large_dir_threshold
+            && !self.options.large_dirs_allowed.contains(directory_path)
+        {
+            let _ = self.sender.send(ScanEvent::LargeDirectory(directory_path.to_path_buf(), entries.len()));
+            return;
+        }
+        self.directories.fetch_add(1, Ordering::Relaxed);
+
+        let entered = enter_directory(ignores, directory_path, &entries);
+        let ignores = match &entered {
+            Some((entered, errors)) => {
+                for (path, error) in errors {
+                    let _ = self.sender.send(ScanEvent::Unreadable(path.display().to_string(), error.clone()));
+                }
+                entered
+            }
+            None => ignores,
+        };
+
+        // Entries are stat'ed and filtered in parallel, and subdirectories fan out
+        // further on the same pool
+        entries.into_par_iter().for_each(|entry| match entry {
+            Ok(entry) => self.scan_entry(entry, depth, ignores),
+            Err(e) => self.unreadable(directory_path, &e),
+        });
+    }
+
+    fn scan_entry(&self, entry: fs::DirEntry, depth: usize, ignores: &IgnoreStack) {
+        if self.progress.is_cancelled() {
+            return;
+        }
+
+        let now = self.now;
+        let time_limit = self.options.time_limit;
+
+        let file_name = entry.file_name();
+        let file_name_str = file_name.to_string_lossy().to_string();
+        let path = entry.path();
+
+        // Skip hidden files and directories
+        if self.options.skips_hidden(&entry) || self.options.is_protected(&path) {
+            return;
+        }
+        // The rules file itself is never up for deletion
+        if file_name_str == cleanignore::FILE_NAME {
+            return;
+        }
+        if self.options.exclude.is_match(&path) || ignores.is_ignored(&path, is_dir_entry(&entry)) {
+            self.excluded.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        // The directory iterator already knows the entry type (d_type on Linux,
+        // the find data on Windows), so only symlinks need a stat to resolve
+        let (metadata, is_symlink) = match entry.file_type() {
+            Ok(file_type) if file_type.is_symlink() => (fs::metadata(&path), true),
+            Ok(_) => (entry.metadata(), false),
+            Err(e) => (Err(e), false),
+        };
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) if is_symlink && e.kind() == std::io::ErrorKind::NotFound => {
+                self.broken_symlinks.fetch_add(1, Ordering::Relaxed);
+                self.found_broken_symlink(path, file_name_str);
+                return;
+            }
+            Err(e) => {
+                self.unreadable(&path, &e);
+                return;
+            }
+        };
+
+        // Bundles are reported as one item instead of being descended into
+        let bundle = metadata.is_dir() && is_bundle(&file_name_str);
+        if bundle && is_symlink && !self.options.follow_symlinks {
+            return;
+        }
+
+        // If it's a directory, recurse into it
+        if metadata.is_dir() && !bundle {
+            let too_deep = self.options.max_depth.is_some_and(|max| depth >= max);
+            if (!is_symlink || self.options.follow_symlinks) && !too_deep {
+                self.scan_directory_recursive(&path, depth + 1, ignores);
+            }
+            return;
+        }
+
+        // A followed link to a bundle is measured at its target
+        let size = match (bundle, is_symlink) {
+            (true, true) => fs::canonicalize(&path).map_or(0, |target| path_size(&target)),
+            (true, false) => path_size(&path),
+            (false, _) => metadata.len(),
+        };
+        self.progress.processed.fetch_add(1, Ordering::Relaxed);
+        self.bytes_scanned.fetch_add(size, Ordering::Relaxed);
+
+        if self.options.min_size_bytes.is_some_and(|min| size < min)
+            || self.options.max_size_bytes.is_some_and(|max| size > max)
+        {
+            return;
+        }
+        if self.modified_recently(metadata.modified().ok()) {
+            return;
+        }
+        // The include list keeps everything else out, partial downloads too
+        if self.options.junk.is_none()
+            && !self.options.include_extensions.is_empty()
+            && !self.options.is_included(&path)
+        {
+            self.filtered.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        // Abandoned partial downloads are their own category. This runs before the
+        // smart filter since some browsers use `.tmp`, which it would drop.
+        if self.options.incomplete_downloads_enabled && is_incomplete_download(&file_name_str) {
+            // Modified time stops moving once a download is abandoned
+            let modified = metadata.modified().unwrap_or(now);
+            let days_idle = now.duration_since(modified).unwrap_or_default().as_secs() / (60 * 60 * 24);
+
+            if days_idle >= self.options.incomplete_download_days {
+                self.found(ScanResult {
+                    file_path: path,
+                    file_name: file_name_str,
+                    should_delete: true,
+                    days_since_access: days_idle,
+                    last_used: modified,
+                    size_bytes: size,
+                    use_count: None,
+                    flag_reason: format!("Incomplete download, untouched for {} days", days_idle),
+                    incomplete_download: true,
+                    empty: size == 0,
+                    broken_symlink: false,
+                    duplicate_group: None,
+                    kept: false,
+                    volume: None,
+                    delete_error: None,
+                });
+            }
+            return;
+        }
+
+        // Apply smart filter to exclude binary/system files. The Junk preset replaces
+        // it with its own list, which is mostly things the smart filter would drop.
+        let filtered = match self.options.junk {
+            Some(_) => !self.options.is_junk(&file_name_str),
+            // Anything not on the include list is already gone
+            None if !self.options.include_extensions.is_empty() => false,
+            None => match self.smart_filter_category(&file_name_str, ignores.in_repository()) {
+                Some(category) => {
+                    self.filtered_by[category as usize].fetch_add(1, Ordering::Relaxed);
+                    if self.options.list_filtered {
+                        let _ = self.sender.send(ScanEvent::Filtered(path.clone(), category));
+                    }
+                    true
+                }
+                None => false,
+            },
+        };
+        if filtered {
+            self.filtered.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        let basis = self.options.time_basis;
+        let (timestamp, verb) = match self.options.time_combiner {
+            TimeCombiner::Single => {
+                // Creation time is missing on many Linux filesystems, and access time
+                // on some network and FUSE ones
+                let Some((timestamp, used)) = basis.read_or_fallback(|basis| basis.read(&metadata)) else {
+                    return;
+                };
+                if used != basis {
+                    self.timestamp_fallbacks.fetch_add(1, Ordering::Relaxed);
+                }
+                (timestamp, used.verb())
+            }
+            combiner => {
+                let Some((modified, used)) = TimeBasis::Modified.read_or_fallback(|basis| basis.read(&metadata)) else {
+                    return;
+                };
+                match metadata.accessed() {
+                    // Both old means the newer one is old; either old means the older one is
+                    Ok(accessed) => match combiner {
+                        TimeCombiner::Both => (accessed.max(modified), combiner.verb(basis)),
+                        _ if accessed <= modified => (accessed, TimeBasis::Accessed.verb()),
+                        _ => (modified, used.verb()),
+                    },
+                    // Nothing to combine without an access time
+                    Err(_) => {
+                        self.timestamp_fallbacks.fetch_add(1, Ordering::Relaxed);
+                        (modified, used.verb())
+                    }
+                }
+            }
+        };
+
+        // A threshold reaching back before the epoch makes everything recent
+        let cutoff = now.checked_sub(time_limit);
+        let recently_accessed = cutoff.is_none_or(|cutoff| timestamp >= cutoff);
+
+        // Calculate days since the chosen timestamp
+        let duration = now
+            .duration_since(timestamp)
+            .unwrap_or_default();
+        let days_since_access = duration.as_secs() / (60 * 60 * 24);
+
+        let use_count = if self.options.usage_signal_enabled {
+            usage::use_count(&path)
+        } else {
+            None
+        };
+
+        let flag_reason = match use_count {
+            // Frequently used files are kept no matter how long ago that was
+            Some(count) if count >= self.options.frequent_use_count => None,
+            // A file opened at most once since it arrived long ago is still junk
+            Some(count) if recently_accessed => {
+                let added = metadata.created().or_else(|_| metadata.modified()).unwrap_or(now);
+                let days_since_added = now.duration_since(added).unwrap_or_default().as_secs() / (60 * 60 * 24);
+                (count <= 1 && cutoff.is_some_and(|cutoff| added <= cutoff)).then(|| format!(
+                    "Opened only {} time(s) since it was added {} days ago",
+                    count, days_since_added))
+            }
+            Some(count) => Some(format!(
+                "Not {} in {} days and used only {} time(s)",
+                verb, days_since_access, count)),
+            None if recently_accessed => None,
+            None => Some(format!("Not {} in {} days", verb, days_since_access)),
+        };
+
+        if self.options.duplicates_enabled && !bundle {
+            self.hashable.lock().unwrap_or_else(|e| e.into_inner())
+                .push((path.clone(), size, timestamp, flag_reason.is_some()));
+        }
+
+        if let Some(flag_reason) = flag_reason {
+            self.found(ScanResult {
+                file_path: path,
+                file_name: file_name_str,
+                should_delete: self.options.preselect_results,
+                days_since_access,
+                last_used: timestamp,
+                size_bytes: size,
+                use_count,
+                flag_reason,
+                incomplete_download: false,
+                empty: size == 0 && !bundle,
+                broken_symlink: false,
+                duplicate_group: None,
+                kept: false,
+                volume: None,
+                delete_error: None,
+            });
+        } else {
+            self.too_recent.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // Sends the copies in each duplicate group that weren't already reported for
+    // their age, so no path is sent twice. The first of those by path is the one
+    // kept and the others are selected for deletion; a group with fewer than two
+    // left has nothing to add.
+    fn report_duplicates(&self) {
+        let files = std::mem::take(&mut *self.hashable.lock().unwrap_or_else(|e| e.into_inner()));
+        let last_used: std::collections::HashMap<PathBuf, SystemTime> = files.iter()
+            .map(|(path, _, time, _)| (path.clone(), *time))
+            .collect();
+        let reported: HashSet<PathBuf> = files.iter()
+            .filter(|(_, _, _, reported)| *reported)
+            .map(|(path, ..)| path.clone())
+            .collect();
+        let sizes = files.into_iter().map(|(path, size, ..)| (path, size)).collect();
+
+        let groups = duplicates::find_duplicates(sizes).into_iter()
+            .map(|paths| {
+                let copies = paths.len();
+                (copies, paths.into_iter().filter(|path| !reported.contains(path)).collect::<Vec<_>>())
+            })
+            .filter(|(_, paths)| paths.len() >= 2);
+        for (group, (copies, paths)) in groups.enumerate() {
+            let kept = paths[0].display().to_string();
+            for (copy, path) in paths.into_iter().enumerate() {
+                let size_bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                let flag_reason = if copy == 0 {
+                    format!("Kept copy of {} identical files", copies)
+                } else {
+                    format!("Duplicate of {}", kept)
+                };
+                let used = last_used.get(&path).copied().unwrap_or(self.now);
+                self.found(ScanResult {
+                    file_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                    days_since_access: self.now.duration_since(used).unwrap_or_default().as_secs() / (60 * 60 * 24),
+                    last_used: used,
+                    file_path: path,
+                    should_delete: copy > 0,
+                    size_bytes,
+                    use_count: None,
+                    flag_reason,
+                    incomplete_download: false,
+                    // Empty files are never grouped as duplicates
+                    empty: false,
+                    broken_symlink: false,
+                    duplicate_group: Some(group),
+                    kept: false,
+                    volume: None,
+                    delete_error: None,
+                });
+            }
+        }
+    }
+
+    // A dangling link is flagged whatever its age, since there's nothing left to use.
+    // Its own timestamp stands in for the target's.
+    fn found_broken_symlink(&self, path: PathBuf, file_name: String) {
+        if self.options.min_size_bytes.is_some_and(|min| min > 0) {
+            return;
+        }
+        let modified = fs::symlink_metadata(&path).and_then(|m| m.modified()).ok();
+        if self.modified_recently(modified) {
+            return;
+        }
+        let modified = modified.unwrap_or(self.now);
+        let days_since_access = self.now.duration_since(modified).unwrap_or_default().as_secs() / (60 * 60 * 24);
+
+        self.found(ScanResult {
+            file_path: path,
+            file_name,
+            should_delete: self.options.preselect_results,
+            days_since_access,
+            last_used: modified,
+            size_bytes: 0,
+            use_count: None,
+            flag_reason: "Broken link, its target no longer exists".to_string(),
+            incomplete_download: false,
+            empty: false,
+            broken_symlink: true,
+            duplicate_group: None,
+            kept: false,
+            volume: None,
+            delete_error: None,
+        });
+    }
+
+    // A file edited lately is in use even if its access time is stale (noatime,
+    // editors that don't touch it). Nothing inside the window is reported, partial
+    // downloads and broken links included, and it stays out of the duplicate pass.
+    fn modified_recently(&self, modified: Option<SystemTime>) -> bool {
+        let Some(window) = self.options.protect_modified_within else {
+            return false;
+        };
+        let recent = modified.is_some_and(|modified| self.now.checked_sub(window).is_none_or(|cutoff| modified >= cutoff));
+        if recent {
+            self.too_recent.fetch_add(1, Ordering::Relaxed);
+        }
+        recent
+    }
+
+    fn found(&self, mut result: ScanResult) {
+        if self.options.keep.contains(&result.file_path) {
+            result.kept = true;
+            result.should_delete = false;
+        }
+        result.volume = self.volumes.kind_of(&result.file_path);
+        let _ = self.sender.send(ScanEvent::Found(result));
+    }
+
+    fn unreadable(&self, path: &Path, error: &std::io::Error) {
+        let _ = self.sender.send(ScanEvent::Unreadable(path.display().to_string(), error.to_string()));
+    }
+
+    // In a git project honoring .gitignore, that takes the place of the build folder names
+    fn smart_filter_category(&self, file_name: &str, in_repository: bool) -> Option<FilterCategory> {
+        if !self.options.smart_filter_enabled {
+            return None;
+        }
+
+        let file_lower = file_name.to_lowercase();
+        let rules = &self.options.filter_rules;
+
+        // Binary and supporting files by extension, then system/cache and build
+        // directory names anywhere in the name
+        if rules.binary_extensions().any(|ext| file_lower.ends_with(&ext)) {
+            Some(FilterCategory::Binary)
+        } else if rules.system_patterns().any(|pattern| file_lower.contains(&pattern)) {
+            Some(FilterCategory::System)
+        } else if !in_repository && rules.build_patterns().any(|pattern| file_lower.contains(&pattern)) {
+            Some(FilterCategory::Build)
+        } else {
+            None
+        }
+    }
+}
+
+// First pass: counts the files the scan will look at, skipping the same hidden
+// entries and held-back large directories so the total matches the real pass
+fn count_files(
+    directory_path: &Path,
+    depth: usize,
+    options: &ScanOptions,
+    progress: &ScanProgress,
+    visited: &Mutex<HashSet<PathBuf>>,
+    ignores: &IgnoreStack,
+) {
+    if progress.is_cancelled() || options.is_protected(directory_path) || !first_visit(directory_path, visited) {
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(directory_path) else {
+        return;
+    };
+    let entries: Vec<_> = entries.collect();
+
+    if entries.len() > options.large_dir_threshold
+        && !options.large_dirs_allowed.contains(directory_path)
+    {
+        return;
+    }
+
+    let entered = enter_directory(ignores, directory_path, &entries);
+    let ignores = entered.as_ref().map_or(ignores, |(entered, _)| entered);
+
+    entries.into_par_iter().flatten().for_each(|entry| {
+        if options.skips_hidden(&entry)
+            || options.exclude.is_match(entry.path())
+            || options.is_protected(&entry.path())
+            || entry.file_name() == cleanignore::FILE_NAME
+            || ignores.is_ignored(&entry.path(), is_dir_entry(&entry))
+        {
+            return;
+        }
+
+        let is_dir = match entry.file_type() {
+            Ok(file_type) if file_type.is_symlink() => match fs::metadata(entry.path()) {
+                Ok(metadata) if metadata.is_dir() && !options.follow_symlinks => return,
+                Ok(metadata) => metadata.is_dir(),
+                Err(_) => return,
+            },
+            Ok(file_type) => file_type.is_dir(),
+            Err(_) => return,
+        };
+
+        if is_dir && is_bundle(&entry.file_name().to_string_lossy()) {
+            progress.total.fetch_add(1, Ordering::Relaxed);
+        } else if is_dir {
+            if options.max_depth.is_none_or(|max| depth < max) {
+                count_files(&entry.path(), depth + 1, options, progress, visited, ignores);
+            }
+        } else {
+            progress.total.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+}
+
+// Ignore files are looked for among the entries already read, so folders without
+// one cost nothing extra
+fn enter_directory(
+    ignores: &IgnoreStack,
+    directory_path: &Path,
+    entries: &[std::io::Result<fs::DirEntry>],
+) -> Option<(IgnoreStack, Vec<(PathBuf, String)>)> {
+    ignores.enter(directory_path, |name| entries.iter().flatten().any(|entry| entry.file_name() == name))
+}
+
+// For folder-only rules like `build/`. Links count as files here, which is what
+// .gitignore does too.
+fn is_dir_entry(entry: &fs::DirEntry) -> bool {
+    entry.file_type().is_ok_and(|file_type| file_type.is_dir())
+}
+
+// Compiles the exclude patterns, returning the ones that aren't valid globs
+// alongside the set built from the rest. Blank lines are ignored.
+pub fn exclude_globs(patterns: &[String]) -> (GlobSet, Vec<String>) {
+    build_globs(patterns, false)
+}
+
+// The junk ruleset is matched against file names alone, ignoring case, since
+// Windows and macOS write the same names in whatever case they like
+pub fn junk_globs(patterns: &[String]) -> (GlobSet, Vec<String>) {
+    build_globs(patterns, true)
+}
+
+fn build_globs(patterns: &[String], case_insensitive: bool) -> (GlobSet, Vec<String>) {
+    let mut builder = GlobSetBuilder::new();
+    let mut invalid = Vec::new();
+
+    for pattern in patterns.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        match GlobBuilder::new(pattern).case_insensitive(case_insensitive).build() {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(_) => invalid.push(pattern.to_string()),
+        }
+    }
+
+    (builder.build().unwrap_or_else(|_| GlobSet::empty()), invalid)
+}
+
+// Extensions as typed (".ZIP", " iso") to the form ScanOptions compares against
+pub fn extension_list(entries: &[String]) -> Vec<String> {
+    entries.iter()
+        .map(|entry| entry.trim().trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect()
+}
+
+// Turns the protected path list into prefixes, dropping blank lines
+pub fn protected_prefixes(paths: &[String]) -> Vec<PathBuf> {
+    paths.iter()
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+// Compares whole components, so /usr protects /usr/lib but not /usr-backup
+pub fn is_protected(path: &Path, protected: &[PathBuf]) -> bool {
+    protected.iter().any(|prefix| path.starts_with(prefix))
+}
+
+// Records `directory_path` as entered, returning false if it (or wherever it really
+// points) has been entered before. Paths that can't be resolved are let through so
+// reading them reports the actual error.
+fn first_visit(directory_path: &Path, visited: &Mutex<HashSet<PathBuf>>) -> bool {
+    let Ok(canonical) = fs::canonicalize(directory_path) else {
+        return true;
+    };
+    visited.lock().unwrap_or_else(|e| e.into_inner()).insert(canonical)
+}
+
+// Dotfiles everywhere, plus anything with the hidden attribute on Windows
+fn is_hidden(entry: &fs::DirEntry) -> bool {
+    if entry.file_name().to_string_lossy().starts_with('.') {
+        return true;
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        // Comes from the directory listing on Windows, so this doesn't cost a stat
+        if entry.metadata().is_ok_and(|metadata| metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0) {
+            return true;
+        }
+    }
+
+    false
+}
+
+// Directories macOS shows as a single item (apps, plug-ins, frameworks). Deleting
+// only part of one breaks it, so they are flagged whole, by the bundle's own timestamp.
+fn is_bundle(file_name: &str) -> bool {
+    let bundle_extensions = [".app", ".bundle", ".framework"];
+
+    let file_lower = file_name.to_lowercase();
+    cfg!(target_os = "macos") && bundle_extensions.iter().any(|ext| file_lower.ends_with(ext))
+}
+
+// Total size of a file or directory tree. Symlinks count as themselves, not what
+// they point to, so a link out of a bundle doesn't inflate its size.
+pub fn path_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| path_size(&entry.path()))
+        .sum()
+}
+
+// What the Junk preset looks for: partial downloads, Office lock files, folder
+// thumbnails and view settings, and installers that have done their job
+pub fn default_junk_patterns() -> Vec<String> {
+    [
+        "*.crdownload", "*.part", "*.partial", "*.download",
+        "~$*",
+        "Thumbs.db", "desktop.ini", ".DS_Store",
+        "*.dmg", "*.pkg", "*.msi", "*.exe",
+    ]
+    .iter()
+    .map(|pattern| pattern.to_string())
+    .collect()
+}
+
+fn is_incomplete_download(file_name: &str) -> bool {
+    // Chrome, Firefox, Safari, Opera, old Edge, and the generic temp suffix
+    let partial_extensions = [
+        ".crdownload", ".part", ".download", ".opdownload", ".partial", ".tmp",
+    ];
+
+    let file_lower = file_name.to_lowercase();
+    partial_extensions.iter().any(|ext| file_lower.ends_with(ext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Stands in for a filesystem that only keeps some of the timestamps
+    fn timestamps(missing: &[TimeBasis]) -> impl Fn(TimeBasis) -> std::io::Result<SystemTime> + '_ {
+        move |basis| {
+            if missing.contains(&basis) {
+                return Err(std::io::Error::from(std::io::ErrorKind::Unsupported));
+            }
+            let days = match basis {
+                TimeBasis::Accessed => 1,
+                TimeBasis::Modified => 2,
+                TimeBasis::Created => 3,
+            };
+            Ok(SystemTime::UNIX_EPOCH + Duration::from_secs(days * 60 * 60 * 24))
+        }
+    }
+
+    #[test]
+    fn uses_the_chosen_timestamp_when_present() {
+        let (_, used) = TimeBasis::Accessed.read_or_fallback(timestamps(&[])).unwrap();
+        assert!(used == TimeBasis::Accessed);
+    }
+
+    #[test]
+    fn falls_back_to_modified_without_access_time() {
+        let (time, used) = TimeBasis::Accessed.read_or_fallback(timestamps(&[TimeBasis::Accessed])).unwrap();
+        assert!(used == TimeBasis::Modified);
+        assert_eq!(time, SystemTime::UNIX_EPOCH + Duration::from_secs(2 * 60 * 60 * 24));
+    }
+
+    #[test]
+    fn falls_back_to_created_without_access_or_modified_time() {
+        let missing = [TimeBasis::Accessed, TimeBasis::Modified];
+        let (_, used) = TimeBasis::Accessed.read_or_fallback(timestamps(&missing)).unwrap();
+        assert!(used == TimeBasis::Created);
+    }
+
+    #[test]
+    fn gives_up_without_any_timestamp() {
+        let missing = TimeBasis::ALL;
+        assert!(TimeBasis::Accessed.read_or_fallback(timestamps(&missing)).is_none());
+    }
+}
@@ -0,0 +1,124 @@
+// Scans that run on their own every few days while the app is open or in the tray,
+// ending in a system notification with what they found
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, SystemTime};
+
+use eframe::egui;
+
+use crate::scanner::{self, ScanEvent, ScanProgress, ScanResult};
+
+// How often the thread wakes to check whether it's been stopped or a scan is due
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+// What a scheduled scan found, waiting for the user to look at it
+pub struct ScheduledResults {
+    pub finished_at: SystemTime,
+    pub directories: Vec<PathBuf>,
+    pub results: Vec<ScanResult>,
+}
+
+// The thread stops within a minute of this being dropped
+pub struct ScanSchedule {
+    pub interval_days: u64,
+    stop: Arc<AtomicBool>,
+    receiver: Receiver<ScheduledResults>,
+}
+
+impl ScanSchedule {
+    // The first scan is due `interval_days` after `last_run`. Settings are read from
+    // disk for each scan, so they match whatever the window saved last.
+    pub fn start(interval_days: u64, last_run: SystemTime, ctx: egui::Context) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        let interval = Duration::from_secs(interval_days * 24 * 60 * 60);
+
+        let stopped = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            let mut due = last_run + interval;
+            loop {
+                while SystemTime::now() < due {
+                    if stopped.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                if stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+
+                let found = run_scan();
+                due = found.finished_at + interval;
+                notify(&found, &ctx);
+                if sender.send(found).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+            }
+        });
+
+        Self { interval_days, stop, receiver }
+    }
+
+    pub fn try_recv(&self) -> Option<ScheduledResults> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl Drop for ScanSchedule {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn run_scan() -> ScheduledResults {
+    let settings = crate::settings::load().unwrap_or_default();
+    let directories: Vec<PathBuf> = settings.search_directories().into_iter().map(PathBuf::from).collect();
+    let receiver = scanner::spawn_scan(
+        directories.clone(), Vec::new(), settings.scan_options(), Arc::new(ScanProgress::default()));
+
+    let mut results: Vec<ScanResult> = Vec::new();
+    for event in receiver {
+        match event {
+            ScanEvent::Found(result) => results.push(result),
+            ScanEvent::Finished(_) | ScanEvent::Failed(_) => break,
+            _ => {}
+        }
+    }
+    results.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+    ScheduledResults { finished_at: SystemTime::now(), directories, results }
+}
+
+// Nothing found means nothing to say
+fn notify(found: &ScheduledResults, ctx: &egui::Context) {
+    if found.results.is_empty() {
+        return;
+    }
+
+    let bytes = found.results.iter().map(|r| r.size_bytes).sum();
+    let shown = notify_rust::Notification::new()
+        .appname("PinnacleSort")
+        .summary("Scheduled scan finished")
+        .body(&format!("{} files ({}) are ready to review.", found.results.len(), crate::human_size(bytes)))
+        .action("default", "Review")
+        .show();
+
+    // Clicking it brings the window back. Waiting blocks, so it gets its own thread.
+    // On macOS waiting would send the notification a second time; the tray icon
+    // brings the window back there instead.
+    #[cfg(not(target_os = "macos"))]
+    if let Ok(handle) = shown {
+        let ctx = ctx.clone();
+        std::thread::spawn(move || handle.wait_for_action(|action| {
+            if action != "__closed" {
+                crate::show_window(&ctx);
+            }
+        }));
+    }
+    #[cfg(target_os = "macos")]
+    let _ = (shown, ctx);
+}
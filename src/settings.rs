@@ -0,0 +1,290 @@
+// Settings that survive restarts, stored as TOML in the platform's config directory
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{DateDisplay, Preset};
+use crate::scanner::{self, FilterRules, ScanOptions, TimeBasis, TimeCombiner, TimeUnit};
+
+// Fields missing from an older config file fall back to their defaults
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // Kept as amount and unit so "2 years" doesn't come back as 730 days. Configs
+    // from before units existed only have the amount, which was always in days.
+    #[serde(alias = "time_limit_days")]
+    pub time_limit: u64,
+    pub time_unit: TimeUnit,
+    pub time_basis: TimeBasis,
+    pub time_combiner: TimeCombiner,
+    pub downloads_enabled: bool,
+    pub documents_enabled: bool,
+    pub desktop_enabled: bool,
+    pub pictures_enabled: bool,
+    pub videos_enabled: bool,
+    pub music_enabled: bool,
+    pub trash_scan_enabled: bool,
+    pub custom_directories: Vec<String>,
+    pub exclude_patterns: Vec<String>,
+    // Skip what .gitignore leaves out inside git projects
+    pub respect_gitignore: bool,
+    // Path prefixes that are never scanned or deleted from, one per entry as typed
+    pub protected_paths: Vec<String>,
+    // Files marked Keep, by full path, and wildcard patterns for more of the same
+    pub never_delete: HashSet<String>,
+    pub never_delete_patterns: Vec<String>,
+    // Network and removable folders the user has agreed to scan despite the warning
+    pub confirmed_external_directories: HashSet<String>,
+    // Where "Archive" moves selected files; empty until the user picks one
+    pub archive_destination: String,
+    // Whether zipping selected files removes them afterwards
+    pub zip_remove_originals: bool,
+    // Where the built-in folders are looked for instead of the OS's home; empty for the OS's
+    pub home_directory: String,
+    // The deletion history is rotated once it grows past this
+    pub history_max_kb: u64,
+    // Days between scans run in the background; unset when they're off
+    pub scheduled_scan_days: Option<u64>,
+    // When the last scheduled scan finished, in seconds since the Unix epoch
+    pub last_scheduled_scan: Option<u64>,
+    // Deleting a file with the trigger extension also removes same-named siblings
+    // with one of the associated extensions. An empty trigger turns this off.
+    pub associated_trigger_extension: String,
+    pub associated_extensions: Vec<String>,
+    pub smart_filter_enabled: bool,
+    pub filter_rules: FilterRules,
+    // File name patterns the Junk preset reports, and nothing else
+    pub junk_patterns: Vec<String>,
+    pub preset: Preset,
+    pub preselect_results: bool,
+    pub usage_signal_enabled: bool,
+    pub large_dir_threshold: usize,
+    pub follow_symlinks: bool,
+    pub include_hidden: bool,
+    pub max_depth: Option<usize>,
+    // Files modified within this many days are kept whatever their age says
+    pub protect_modified_days: Option<u64>,
+    pub duplicates_enabled: bool,
+    pub min_size_mb: Option<u64>,
+    pub max_size_mb: Option<u64>,
+    // Comma-separated in the UI and kept as typed between the commas
+    pub include_extensions: Vec<String>,
+    pub date_display: DateDisplay,
+    // A chrono format string, used when dates are shown as dates
+    pub date_format: String,
+    // Last window geometry in points; unset until the window has been shown once
+    pub window_size: Option<[f32; 2]>,
+    pub window_position: Option<[f32; 2]>,
+    // Result tree folders left open, so a rescan or restart doesn't collapse everything
+    pub expanded_folders: HashSet<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            time_limit: 14,
+            time_unit: TimeUnit::Days,
+            time_basis: TimeBasis::Accessed,
+            time_combiner: TimeCombiner::Single,
+            downloads_enabled: true,
+            documents_enabled: true,
+            desktop_enabled: true,
+            pictures_enabled: false,
+            videos_enabled: false,
+            music_enabled: false,
+            trash_scan_enabled: false,
+            custom_directories: Vec::new(),
+            exclude_patterns: Vec::new(),
+            respect_gitignore: false,
+            protected_paths: default_protected_paths(),
+            never_delete: HashSet::new(),
+            never_delete_patterns: Vec::new(),
+            confirmed_external_directories: HashSet::new(),
+            archive_destination: String::new(),
+            zip_remove_originals: false,
+            home_directory: String::new(),
+            history_max_kb: 1024,
+            scheduled_scan_days: None,
+            last_scheduled_scan: None,
+            associated_trigger_extension: "exe".to_string(),
+            associated_extensions: ["dll", "dat", "ini", "cfg", "config"]
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+            smart_filter_enabled: true,
+            filter_rules: FilterRules::default(),
+            junk_patterns: crate::scanner::default_junk_patterns(),
+            preset: Preset::Balanced,
+            preselect_results: true,
+            usage_signal_enabled: false,
+            large_dir_threshold: 10_000,
+            follow_symlinks: true,
+            include_hidden: false,
+            max_depth: None,
+            protect_modified_days: None,
+            duplicates_enabled: false,
+            min_size_mb: None,
+            max_size_mb: None,
+            include_extensions: Vec::new(),
+            date_display: DateDisplay::Relative,
+            date_format: "%Y-%m-%d".to_string(),
+            window_size: None,
+            window_position: None,
+            expanded_folders: HashSet::new(),
+        }
+    }
+}
+
+impl Settings {
+    // The built-in folders that are switched on, followed by the custom ones
+    pub fn search_directories(&self) -> Vec<String> {
+        let mut directories = Vec::new();
+        if self.downloads_enabled {
+            directories.push(user_dir(&self.home_directory, directories::UserDirs::download_dir, "Downloads"));
+        }
+        if self.documents_enabled {
+            directories.push(user_dir(&self.home_directory, directories::UserDirs::document_dir, "Documents"));
+        }
+        if self.desktop_enabled {
+            directories.push(user_dir(&self.home_directory, directories::UserDirs::desktop_dir, "Desktop"));
+        }
+        if self.pictures_enabled {
+            directories.push(user_dir(&self.home_directory, directories::UserDirs::picture_dir, "Pictures"));
+        }
+        if self.videos_enabled {
+            directories.push(user_dir(&self.home_directory, directories::UserDirs::video_dir, "Videos"));
+        }
+        if self.music_enabled {
+            directories.push(user_dir(&self.home_directory, directories::UserDirs::audio_dir, "Music"));
+        }
+        if self.trash_scan_enabled {
+            directories.extend(crate::trash_bin::trash_directories()
+                .into_iter()
+                .map(|dir| dir.to_string_lossy().to_string()));
+        }
+        directories.extend(self.custom_directories.iter().cloned());
+        directories
+    }
+
+    // What scans run without the window use, headless ones and scheduled ones
+    pub fn scan_options(&self) -> ScanOptions {
+        ScanOptions {
+            time_limit: self.time_unit.duration(self.time_limit),
+            time_basis: self.time_basis,
+            time_combiner: self.time_combiner,
+            protect_modified_within: self.protect_modified_days.map(|days| TimeUnit::Days.duration(days)),
+            smart_filter_enabled: self.smart_filter_enabled,
+            filter_rules: self.filter_rules.clone(),
+            preselect_results: self.preselect_results,
+            usage_signal_enabled: self.usage_signal_enabled,
+            frequent_use_count: 10,
+            large_dir_threshold: self.large_dir_threshold,
+            large_dirs_allowed: HashSet::new(),
+            incomplete_downloads_enabled: true,
+            incomplete_download_days: 1,
+            follow_symlinks: self.follow_symlinks,
+            include_hidden: self.include_hidden,
+            max_depth: self.max_depth,
+            duplicates_enabled: self.duplicates_enabled,
+            exclude: scanner::exclude_globs(&self.exclude_patterns).0,
+            respect_gitignore: self.respect_gitignore,
+            min_size_bytes: self.min_size_mb.map(|mb| mb.saturating_mul(1024 * 1024)),
+            max_size_bytes: self.max_size_mb.map(|mb| mb.saturating_mul(1024 * 1024)),
+            include_extensions: scanner::extension_list(&self.include_extensions),
+            protected: scanner::protected_prefixes(&self.protected_paths),
+            keep: scanner::KeepList::new(&self.never_delete, &self.never_delete_patterns),
+            junk: (self.preset == Preset::Junk).then(|| scanner::junk_globs(&self.junk_patterns).0),
+            list_filtered: false,
+        }
+    }
+}
+
+// The operating system's own folders, plus the user's config directory (which
+// holds this app's settings among everyone else's)
+fn default_protected_paths() -> Vec<String> {
+    let system: &[&str] = if cfg!(target_os = "windows") {
+        &[r"C:\Windows", r"C:\Program Files", r"C:\Program Files (x86)", r"C:\ProgramData"]
+    } else if cfg!(target_os = "macos") {
+        &["/System", "/Library", "/bin", "/sbin", "/usr", "/private/etc", "/private/var/db"]
+    } else {
+        &["/bin", "/boot", "/dev", "/etc", "/lib", "/lib64", "/proc", "/sbin", "/sys", "/usr"]
+    };
+
+    let mut paths: Vec<String> = system.iter().map(|path| path.to_string()).collect();
+    if let Some(dirs) = directories::BaseDirs::new() {
+        paths.push(dirs.config_dir().to_string_lossy().to_string());
+    }
+    paths
+}
+
+// Built-in folders come from the OS, which knows their localized names and where
+// they were moved to. With a home override each one is looked up at the same
+// place under the new home. The English name under the home folder is the
+// fallback, also for folders the OS maps to the home folder itself.
+pub fn user_dir(
+    home_override: &str,
+    pick: fn(&directories::UserDirs) -> Option<&Path>,
+    fallback: &str,
+) -> String {
+    let home = home_dir(home_override);
+    let dir = directories::UserDirs::new().and_then(|dirs| {
+        let dir = pick(&dirs)?;
+        if dir == dirs.home_dir() {
+            None
+        } else if home_override.trim().is_empty() {
+            Some(dir.to_path_buf())
+        } else {
+            Some(home.join(dir.strip_prefix(dirs.home_dir()).ok()?))
+        }
+    });
+    dir.unwrap_or_else(|| home.join(fallback)).to_string_lossy().to_string()
+}
+
+pub fn home_dir(home_override: &str) -> PathBuf {
+    let home_override = home_override.trim();
+    if !home_override.is_empty() {
+        return PathBuf::from(home_override);
+    }
+    if let Some(dirs) = directories::BaseDirs::new() {
+        return dirs.home_dir().to_path_buf();
+    }
+
+    // No home directory in the environment; guess from the user name
+    let user = whoami::username();
+    if cfg!(target_os = "windows") {
+        PathBuf::from(format!("C:\\Users\\{}", user))
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(format!("/Users/{}", user))
+    } else {
+        PathBuf::from(format!("/home/{}", user))
+    }
+}
+
+// e.g. ~/.config/pinnaclesort on Linux
+pub fn config_dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("com", "NotMaxwell", "PinnacleSort")
+        .map(|dirs| dirs.config_dir().to_path_buf())
+}
+
+fn config_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("settings.toml"))
+}
+
+// A missing or unreadable config isn't an error; the app just starts from defaults
+pub fn load() -> Option<Settings> {
+    let contents = fs::read_to_string(config_path()?).ok()?;
+    toml::from_str(&contents).ok()
+}
+
+pub fn save(settings: &Settings) -> Result<(), String> {
+    let path = config_path().ok_or_else(|| "no config directory for this user".to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let contents = toml::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(path, contents).map_err(|e| e.to_string())
+}
@@ -0,0 +1,114 @@
+// Small previews of image results, shown when hovering a row. Images are decoded
+// off the UI thread the first time they're hovered, and only the most recently
+// shown ones are kept as textures.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+// Longest side of a preview, in pixels
+const SIZE: u32 = 160;
+// Textures kept before the least recently shown is dropped, about 6 MB at most
+const CAPACITY: usize = 64;
+// Larger files aren't decoded just for a preview
+const MAX_FILE_BYTES: u64 = 50 * 1024 * 1024;
+
+const EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "webp", "bmp", "ico"];
+
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+pub struct Thumbnails {
+    // None for files that couldn't be decoded, so they aren't tried again
+    textures: HashMap<PathBuf, Option<egui::TextureHandle>>,
+    // Least recently shown first
+    order: VecDeque<PathBuf>,
+    pending: HashSet<PathBuf>,
+    sender: Sender<(PathBuf, Option<egui::ColorImage>)>,
+    receiver: Receiver<(PathBuf, Option<egui::ColorImage>)>,
+}
+
+impl Default for Thumbnails {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            textures: HashMap::new(),
+            order: VecDeque::new(),
+            pending: HashSet::new(),
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl Thumbnails {
+    // Draws the preview, starting the decode if it hasn't been asked for yet
+    pub fn show(&mut self, ui: &mut egui::Ui, path: &Path) {
+        self.receive(ui.ctx());
+
+        match self.textures.get(path) {
+            Some(Some(texture)) => {
+                ui.image((texture.id(), texture.size_vec2()));
+                self.touch(path);
+            }
+            Some(None) => {}
+            None => {
+                self.request(ui.ctx(), path);
+                ui.label(egui::RichText::new("Loading preview…")
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(120, 120, 120)));
+            }
+        }
+    }
+
+    fn request(&mut self, ctx: &egui::Context, path: &Path) {
+        if !self.pending.insert(path.to_path_buf()) {
+            return;
+        }
+        let path = path.to_path_buf();
+        let sender = self.sender.clone();
+        let ctx = ctx.clone();
+        rayon::spawn(move || {
+            let image = decode(&path);
+            let _ = sender.send((path, image));
+            ctx.request_repaint();
+        });
+    }
+
+    fn receive(&mut self, ctx: &egui::Context) {
+        while let Ok((path, image)) = self.receiver.try_recv() {
+            self.pending.remove(&path);
+            let texture = image.map(|image| {
+                ctx.load_texture(format!("thumbnail:{}", path.display()), image, egui::TextureOptions::LINEAR)
+            });
+            self.textures.insert(path.clone(), texture);
+            self.touch(&path);
+        }
+
+        while self.order.len() > CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.textures.remove(&oldest);
+            }
+        }
+    }
+
+    fn touch(&mut self, path: &Path) {
+        if self.order.back().is_some_and(|last| last == path) {
+            return;
+        }
+        self.order.retain(|shown| shown != path);
+        self.order.push_back(path.to_path_buf());
+    }
+}
+
+fn decode(path: &Path) -> Option<egui::ColorImage> {
+    if std::fs::metadata(path).ok()?.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    let image = image::open(path).ok()?.thumbnail(SIZE, SIZE).into_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Some(egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
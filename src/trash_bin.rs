@@ -0,0 +1,196 @@
+// Reporting on and emptying the operating system's Trash / Recycle Bin
+
+#[cfg(not(target_os = "windows"))]
+use std::fs;
+use std::path::PathBuf;
+
+use crate::scanner::path_size;
+
+#[derive(Clone)]
+pub struct TrashVolume {
+    pub location: String,
+    pub size_bytes: u64,
+    pub item_count: usize,
+}
+
+// Freedesktop trash: one trash folder per mounted volume, items live in `files/`
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn query_trash() -> Result<Vec<TrashVolume>, String> {
+    let folders = trash::os_limited::trash_folders().map_err(|e| e.to_string())?;
+
+    let mut volumes: Vec<TrashVolume> = folders
+        .into_iter()
+        .map(|folder| {
+            let files = folder.join("files");
+            let item_count = fs::read_dir(&files).map(|entries| entries.count()).unwrap_or(0);
+            TrashVolume {
+                location: folder.to_string_lossy().to_string(),
+                size_bytes: path_size(&files),
+                item_count,
+            }
+        })
+        .collect();
+
+    volumes.sort_by(|a, b| a.location.cmp(&b.location));
+    Ok(volumes)
+}
+
+// Recycle Bin items stay on the drive they were deleted from, so group by drive
+#[cfg(target_os = "windows")]
+pub fn query_trash() -> Result<Vec<TrashVolume>, String> {
+    let items = trash::os_limited::list().map_err(|e| e.to_string())?;
+    let mut volumes: Vec<TrashVolume> = Vec::new();
+
+    for item in items {
+        let drive = item.original_parent
+            .components()
+            .next()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // Folders only report an entry count, so measure them on disk instead
+        let size_bytes = trash::os_limited::metadata(&item)
+            .ok()
+            .and_then(|metadata| metadata.size.size())
+            .unwrap_or_else(|| path_size(std::path::Path::new(&item.id)));
+
+        match volumes.iter_mut().find(|v| v.location == drive) {
+            Some(volume) => {
+                volume.size_bytes += size_bytes;
+                volume.item_count += 1;
+            }
+            None => volumes.push(TrashVolume {
+                location: drive,
+                size_bytes,
+                item_count: 1,
+            }),
+        }
+    }
+
+    volumes.sort_by(|a, b| a.location.cmp(&b.location));
+    Ok(volumes)
+}
+
+// ~/.Trash for the boot volume, /Volumes/<name>/.Trashes/<uid> for the rest
+#[cfg(target_os = "macos")]
+fn trash_folders() -> Result<Vec<PathBuf>, String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let home = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| "HOME is not set".to_string())?;
+    let uid = fs::metadata(&home).map_err(|e| e.to_string())?.uid();
+
+    let mut folders = vec![home.join(".Trash")];
+    if let Ok(mounts) = fs::read_dir("/Volumes") {
+        for mount in mounts.filter_map(|entry| entry.ok()) {
+            let folder = mount.path().join(".Trashes").join(uid.to_string());
+            if folder.is_dir() {
+                folders.push(folder);
+            }
+        }
+    }
+    Ok(folders)
+}
+
+#[cfg(target_os = "macos")]
+pub fn query_trash() -> Result<Vec<TrashVolume>, String> {
+    let mut volumes = Vec::new();
+    for folder in trash_folders()? {
+        let entries = match fs::read_dir(&folder) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                return Err("Full Disk Access is required to read the Trash".to_string());
+            }
+            Err(_) => continue,
+        };
+
+        let items: Vec<_> = entries.filter_map(|entry| entry.ok()).collect();
+        volumes.push(TrashVolume {
+            location: folder.to_string_lossy().to_string(),
+            size_bytes: items.iter().map(|entry| path_size(&entry.path())).sum(),
+            item_count: items.len(),
+        });
+    }
+
+    Ok(volumes)
+}
+
+// Where trashed files sit on disk, so the Trash can be scanned like any other folder
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn trash_directories() -> Vec<PathBuf> {
+    let mut folders: Vec<PathBuf> = trash::os_limited::trash_folders()
+        .map(|folders| folders.into_iter().map(|folder| folder.join("files")).collect())
+        .unwrap_or_default();
+    folders.sort();
+    folders
+}
+
+#[cfg(target_os = "macos")]
+pub fn trash_directories() -> Vec<PathBuf> {
+    trash_folders().unwrap_or_default()
+}
+
+// Recycle Bin entries are stored under generated names, which says nothing useful
+// about the files, so it isn't offered for scanning
+#[cfg(target_os = "windows")]
+pub fn trash_directories() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+pub const CAN_SCAN_TRASH: bool = cfg!(not(target_os = "windows"));
+
+#[cfg(not(target_os = "macos"))]
+pub fn empty_trash() -> Result<(), String> {
+    let items = trash::os_limited::list().map_err(|e| e.to_string())?;
+    trash::os_limited::purge_all(items).map_err(|e| e.to_string())
+}
+
+// The trash crate can't purge on macOS, so ask Finder to do it
+#[cfg(target_os = "macos")]
+pub fn empty_trash() -> Result<(), String> {
+    let output = std::process::Command::new("osascript")
+        .args(["-e", "tell application \"Finder\" to empty trash"])
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+// Whether items can be put back from the Trash programmatically on this platform
+pub const CAN_RESTORE: bool = cfg!(not(target_os = "macos"));
+
+// Puts the given paths back where they were deleted from. When the same path was
+// trashed more than once, the most recent copy wins.
+#[cfg(not(target_os = "macos"))]
+pub fn restore(paths: &[std::path::PathBuf]) -> Result<usize, String> {
+    let mut newest: std::collections::HashMap<std::path::PathBuf, trash::TrashItem> =
+        std::collections::HashMap::new();
+
+    for item in trash::os_limited::list().map_err(|e| e.to_string())? {
+        let original = item.original_path();
+        if !paths.contains(&original) {
+            continue;
+        }
+        match newest.get(&original) {
+            Some(existing) if existing.time_deleted >= item.time_deleted => {}
+            _ => {
+                newest.insert(original, item);
+            }
+        }
+    }
+
+    let restored = newest.len();
+    trash::os_limited::restore_all(newest.into_values()).map_err(|e| e.to_string())?;
+    Ok(restored)
+}
+
+// The trash crate has no restore support on macOS
+#[cfg(target_os = "macos")]
+pub fn restore(_paths: &[std::path::PathBuf]) -> Result<usize, String> {
+    Err("Restoring from the Trash isn't supported on macOS".to_string())
+}
@@ -0,0 +1,71 @@
+// The tray icon that keeps PinnacleSort running while scheduled scans are on.
+// Clicking it brings the window back, and its menu is the way to really quit.
+// Linux is left out since the tray there would need GTK.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use eframe::egui;
+
+pub const SUPPORTED: bool = cfg!(any(target_os = "windows", target_os = "macos"));
+
+// The icon leaves the tray when this is dropped
+pub struct Tray {
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    _icon: tray_icon::TrayIcon,
+    // Set by the menu's Quit, so that closing the window exits instead of hiding it
+    quit: Arc<AtomicBool>,
+}
+
+impl Tray {
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    pub fn new(icon: egui::IconData, ctx: &egui::Context) -> Result<Self, String> {
+        use tray_icon::menu::{Menu, MenuEvent, MenuItem};
+        use tray_icon::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+
+        let open_item = MenuItem::new("Open PinnacleSort", true, None);
+        let quit_item = MenuItem::new("Quit", true, None);
+        let menu = Menu::with_items(&[&open_item, &quit_item]).map_err(|e| e.to_string())?;
+        let icon = tray_icon::Icon::from_rgba(icon.rgba, icon.width, icon.height).map_err(|e| e.to_string())?;
+
+        let tray_icon = TrayIconBuilder::new()
+            .with_icon(icon)
+            .with_tooltip("PinnacleSort")
+            .with_menu(Box::new(menu))
+            .with_menu_on_left_click(false)
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let click_ctx = ctx.clone();
+        TrayIconEvent::set_event_handler(Some(move |event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                crate::show_window(&click_ctx);
+            }
+        }));
+
+        let quit = Arc::new(AtomicBool::new(false));
+        let quitting = Arc::clone(&quit);
+        let (open_id, quit_id) = (open_item.id().clone(), quit_item.id().clone());
+        let menu_ctx = ctx.clone();
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            if event.id == quit_id {
+                quitting.store(true, Ordering::Relaxed);
+                crate::show_window(&menu_ctx);
+                menu_ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            } else if event.id == open_id {
+                crate::show_window(&menu_ctx);
+            }
+        }));
+
+        Ok(Self { _icon: tray_icon, quit })
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    pub fn new(_icon: egui::IconData, _ctx: &egui::Context) -> Result<Self, String> {
+        Err("there's no tray icon on this platform".to_string())
+    }
+
+    pub fn quitting(&self) -> bool {
+        self.quit.load(Ordering::Relaxed)
+    }
+}
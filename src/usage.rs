@@ -0,0 +1,27 @@
+// How often a file has been opened, where the platform keeps track of it
+
+use std::path::Path;
+
+// Spotlight records a use count for documents opened through LaunchServices
+#[cfg(target_os = "macos")]
+pub fn use_count(path: &Path) -> Option<u64> {
+    let output = std::process::Command::new("mdls")
+        .args(["-raw", "-name", "kMDItemUseCount"])
+        .arg(path)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    // Unindexed files report "(null)", which fails to parse and falls back to timestamps
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+// Windows prefetch only covers executables and needs admin rights to read, and Linux
+// has no equivalent, so other platforms fall back to timestamps alone
+#[cfg(not(target_os = "macos"))]
+pub fn use_count(_path: &Path) -> Option<u64> {
+    None
+}
@@ -0,0 +1,197 @@
+// The volumes scan targets live on. Network and removable drives are told apart
+// from local disks, since crawling one can take minutes and deleting from a
+// network share skips the local Trash. Each drive's free space is shown too.
+
+use std::path::{Component, Path, PathBuf, Prefix};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum VolumeKind {
+    Network,
+    Removable,
+}
+
+impl VolumeKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            VolumeKind::Network => "network drive",
+            VolumeKind::Removable => "removable drive",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            VolumeKind::Network => "🌐",
+            VolumeKind::Removable => "💾",
+        }
+    }
+}
+
+// Mount points of the network and removable volumes attached when this was made
+pub struct ExternalVolumes {
+    mounts: Vec<(PathBuf, VolumeKind)>,
+}
+
+impl ExternalVolumes {
+    pub fn detect() -> Self {
+        Self { mounts: external_mounts() }
+    }
+
+    // UNC paths (\\server\share) are always on the network. Otherwise the innermost
+    // mount containing the path decides.
+    pub fn kind_of(&self, path: &Path) -> Option<VolumeKind> {
+        if let Some(Component::Prefix(prefix)) = path.components().next()
+            && matches!(prefix.kind(), Prefix::UNC(..) | Prefix::VerbatimUNC(..))
+        {
+            return Some(VolumeKind::Network);
+        }
+
+        self.mounts
+            .iter()
+            .filter(|(mount, _)| path.starts_with(mount))
+            .max_by_key(|(mount, _)| mount.components().count())
+            .map(|(_, kind)| *kind)
+    }
+}
+
+#[cfg(unix)]
+fn network_filesystem(fs_type: &str) -> bool {
+    const NETWORK: &[&str] = &[
+        "nfs", "nfs4", "cifs", "smb3", "smbfs", "afpfs", "webdav", "afs", "9p", "ceph",
+        "glusterfs", "davfs", "fuse.sshfs", "fuse.rclone", "fuse.davfs2",
+    ];
+    NETWORK.contains(&fs_type)
+}
+
+// Network filesystems by type. Desktops mount USB sticks and card readers under
+// /media or /run/media, which is the closest thing to a removable flag a mount has.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn external_mounts() -> Vec<(PathBuf, VolumeKind)> {
+    let Ok(mounts) = std::fs::read_to_string("/proc/self/mounts") else {
+        return Vec::new();
+    };
+
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (_device, mount_point, fs_type) = (fields.next()?, fields.next()?, fields.next()?);
+            // Spaces and tabs in mount points are written as octal escapes
+            let mount_point = PathBuf::from(mount_point.replace("\\040", " ").replace("\\011", "\t"));
+
+            let kind = if network_filesystem(fs_type) {
+                VolumeKind::Network
+            } else if mount_point.starts_with("/media") || mount_point.starts_with("/run/media") {
+                VolumeKind::Removable
+            } else {
+                return None;
+            };
+            Some((mount_point, kind))
+        })
+        .collect()
+}
+
+// `mount` lists lines like "//me@nas/share on /Volumes/share (smbfs, nodev, ...)".
+// Anything else under /Volumes is an external disk; the boot volume lives elsewhere.
+#[cfg(target_os = "macos")]
+fn external_mounts() -> Vec<(PathBuf, VolumeKind)> {
+    let Ok(output) = std::process::Command::new("/sbin/mount").output() else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (_device, rest) = line.split_once(" on ")?;
+            let (mount_point, options) = rest.rsplit_once(" (")?;
+            let fs_type = options.split(',').next()?;
+            let mount_point = PathBuf::from(mount_point);
+
+            let kind = if network_filesystem(fs_type) {
+                VolumeKind::Network
+            } else if mount_point.starts_with("/Volumes") {
+                VolumeKind::Removable
+            } else {
+                return None;
+            };
+            Some((mount_point, kind))
+        })
+        .collect()
+}
+
+// Drive letters, mapped network drives included
+#[cfg(target_os = "windows")]
+fn external_mounts() -> Vec<(PathBuf, VolumeKind)> {
+    use windows_sys::Win32::Storage::FileSystem::GetDriveTypeW;
+    use windows_sys::Win32::System::WindowsProgramming::{DRIVE_CDROM, DRIVE_REMOTE, DRIVE_REMOVABLE};
+
+    (b'A'..=b'Z')
+        .filter_map(|letter| {
+            let root = format!("{}:\\", letter as char);
+            let wide: Vec<u16> = root.encode_utf16().chain(std::iter::once(0)).collect();
+            // SAFETY: `wide` is NUL-terminated and outlives the call
+            let kind = match unsafe { GetDriveTypeW(wide.as_ptr()) } {
+                DRIVE_REMOTE => VolumeKind::Network,
+                DRIVE_REMOVABLE | DRIVE_CDROM => VolumeKind::Removable,
+                _ => return None,
+            };
+            Some((PathBuf::from(root), kind))
+        })
+        .collect()
+}
+
+pub struct DriveSpace {
+    // Mount point, or drive root on Windows
+    pub root: PathBuf,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+// One entry per distinct volume among `directories`, in the order they're listed.
+// Directories that don't exist or can't be queried are left out.
+pub fn drive_space(directories: &[PathBuf]) -> Vec<DriveSpace> {
+    let mut drives: Vec<DriveSpace> = Vec::new();
+    for directory in directories {
+        let Some(root) = volume_root(directory) else {
+            continue;
+        };
+        if drives.iter().any(|drive| drive.root == root) {
+            continue;
+        }
+        if let (Ok(total_bytes), Ok(available_bytes)) = (fs2::total_space(&root), fs2::available_space(&root)) {
+            drives.push(DriveSpace { root, total_bytes, available_bytes });
+        }
+    }
+    drives
+}
+
+// The topmost ancestor still on the same device
+#[cfg(unix)]
+fn volume_root(path: &Path) -> Option<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let path = std::fs::canonicalize(path).ok()?;
+    let device = std::fs::metadata(&path).ok()?.dev();
+    let mut root = path.as_path();
+    while let Some(parent) = root.parent() {
+        if std::fs::metadata(parent).ok().map(|metadata| metadata.dev()) != Some(device) {
+            break;
+        }
+        root = parent;
+    }
+    Some(root.to_path_buf())
+}
+
+// C:\ or \\server\share\, straight from the path
+#[cfg(windows)]
+fn volume_root(path: &Path) -> Option<PathBuf> {
+    if !path.is_dir() {
+        return None;
+    }
+    let root: PathBuf = path
+        .components()
+        .take_while(|component| matches!(component, Component::Prefix(_) | Component::RootDir))
+        .collect();
+    root.has_root().then_some(root)
+}
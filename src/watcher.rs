@@ -0,0 +1,49 @@
+// Live mode: watches the scan targets and reports when something in them changed
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+
+// The OS watch stops and its thread exits when this is dropped
+pub struct LiveWatcher {
+    _watcher: notify::RecommendedWatcher,
+    changed: Arc<AtomicBool>,
+    pub directories: Vec<PathBuf>,
+}
+
+impl LiveWatcher {
+    // `wake` is called from the watcher's thread on every relevant change, so the UI
+    // can repaint without polling. Directories that can't be watched are left out.
+    pub fn start(
+        directories: Vec<PathBuf>,
+        wake: impl Fn() + Send + 'static,
+    ) -> notify::Result<Self> {
+        let changed = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&changed);
+
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            // Reading files (the duplicate pass hashes them) shows up as access
+            // events, which would otherwise set off a rescan after every scan
+            let Ok(event) = event else {
+                return;
+            };
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+                flag.store(true, Ordering::Relaxed);
+                wake();
+            }
+        })?;
+
+        for directory in &directories {
+            let _ = watcher.watch(directory, RecursiveMode::Recursive);
+        }
+
+        Ok(Self { _watcher: watcher, changed, directories })
+    }
+
+    // Whether anything changed since the last call
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::Relaxed)
+    }
+}